@@ -0,0 +1,99 @@
+//! compare the extraction time of `*.tar.gz` with the sequential implementation,
+//! on a synthetic go archive of 15000 files.
+//!
+//! cargo bench --bench unpack
+
+use std::fs::{self, File};
+use std::path::Path;
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use goup_rs::archived::Unpack;
+use indicatif::MultiProgress;
+use tar::{Archive, Builder, EntryType, Header};
+
+/// the sequential implementation, the baseline.
+fn unpack_sequential(dest_dir: &Path, archive_file: &Path) -> Result<(), anyhow::Error> {
+    let mut archive = Archive::new(GzDecoder::new(File::open(archive_file)?));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?;
+        if let Some(relative_path) = path.to_str().and_then(|v| v.strip_prefix("go/")) {
+            let dest_file = dest_dir.join(relative_path);
+            if let Some(parent) = dest_file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            entry.unpack(dest_file)?;
+        }
+    }
+    Ok(())
+}
+
+/// build a synthetic go archive with `dirs` * `files_per_dir` files.
+fn build_archive(
+    archive_file: &Path,
+    dirs: usize,
+    files_per_dir: usize,
+) -> Result<(), anyhow::Error> {
+    let encoder = GzEncoder::new(File::create(archive_file)?, Compression::fast());
+    let mut builder = Builder::new(encoder);
+    for d in 0..dirs {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Directory);
+        header.set_mode(0o755);
+        header.set_size(0);
+        header.set_cksum();
+        builder.append_data(&mut header, format!("go/src/pkg{d}/"), std::io::empty())?;
+        for f in 0..files_per_dir {
+            let content = format!("package pkg{d}\n\n// file {f}\n").repeat(16);
+            let mut header = Header::new_gnu();
+            header.set_mode(0o644);
+            header.set_size(content.len() as u64);
+            header.set_cksum();
+            builder.append_data(
+                &mut header,
+                format!("go/src/pkg{d}/file{f}.go"),
+                content.as_bytes(),
+            )?;
+        }
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn bench_unpack(c: &mut Criterion) {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let archive_file = tmp_dir.path().join("go.tar.gz");
+    build_archive(&archive_file, 300, 50).unwrap();
+    let unpack = "go.tar.gz".parse::<Unpack>().unwrap();
+    let mp = MultiProgress::new();
+
+    let mut group = c.benchmark_group("unpack 15000 files");
+    group.sample_size(10);
+    // 每次解压到新的目录, 删除目录不计入耗时
+    let dest_dir = || tempfile::tempdir_in(tmp_dir.path()).unwrap();
+    group.bench_function("sequential", |b| {
+        b.iter_batched(
+            dest_dir,
+            |dest| {
+                unpack_sequential(dest.path(), &archive_file).unwrap();
+                dest
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.bench_function("parallel", |b| {
+        b.iter_batched(
+            dest_dir,
+            |dest| {
+                unpack.unpack(dest.path(), &archive_file, &mp).unwrap();
+                dest
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_unpack);
+criterion_main!(benches);
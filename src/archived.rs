@@ -4,6 +4,7 @@ mod zip;
 use std::{path::Path, str::FromStr};
 
 use anyhow::anyhow;
+use indicatif::MultiProgress;
 
 use self::tgz::Tgz;
 use self::zip::Zip;
@@ -11,15 +12,19 @@ use self::zip::Zip;
 /// unpack format,
 /// such as zip,tar.gz.
 pub trait Unpacker {
-    /// unpack the provided archive file to dest_dir.
-    fn unpack<P1, P2>(dest_dir: P1, archive_file: P2) -> Result<(), anyhow::Error>
+    /// unpack the provided archive file to dest_dir, report the progress through `mp`.
+    fn unpack<P1, P2>(
+        dest_dir: P1,
+        archive_file: P2,
+        mp: &MultiProgress,
+    ) -> Result<(), anyhow::Error>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>;
 }
 
 /// Unpack the provided archive file.
-pub(crate) enum Unpack {
+pub enum Unpack {
     Zip,
     Tgz,
 }
@@ -39,14 +44,19 @@ impl FromStr for Unpack {
 }
 
 impl Unpack {
-    pub(crate) fn unpack<P1, P2>(&self, dest_dir: P1, archive_file: P2) -> Result<(), anyhow::Error>
+    pub fn unpack<P1, P2>(
+        &self,
+        dest_dir: P1,
+        archive_file: P2,
+        mp: &MultiProgress,
+    ) -> Result<(), anyhow::Error>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>,
     {
         match self {
-            Unpack::Zip => Zip::unpack(dest_dir, archive_file),
-            Unpack::Tgz => Tgz::unpack(dest_dir, archive_file),
+            Unpack::Zip => Zip::unpack(dest_dir, archive_file, mp),
+            Unpack::Tgz => Tgz::unpack(dest_dir, archive_file, mp),
         }
     }
 }
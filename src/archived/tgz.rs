@@ -1,37 +1,275 @@
+use std::collections::HashSet;
 use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, SystemTime};
 use std::{fs::File, path::Path};
 
 use anyhow::anyhow;
 use flate2::read::GzDecoder;
-use tar::Archive;
+use indicatif::MultiProgress;
+use tar::{Archive, EntryType};
 
 use super::Unpacker;
+use crate::progress::Progress;
+
+/// max number of file writer workers.
+const MAX_WORKERS: usize = 8;
+/// number of pending files per worker, bound the memory hold by the queue.
+const QUEUE_DEPTH_PER_WORKER: usize = 16;
 
 /// archive *.tar.gz
+///
+/// the archive is decompressed sequentially, directories are created up front
+/// by the decompressing thread, regular files are handed to a bounded pool of writer.
 pub(crate) struct Tgz;
 
+/// a regular file which is ready to write.
+struct FileTask {
+    path: PathBuf,
+    data: Vec<u8>,
+    mode: Option<u32>,
+    mtime: Option<u64>,
+}
+
 impl Unpacker for Tgz {
-    fn unpack<P1, P2>(dest_dir: P1, archive_file: P2) -> Result<(), anyhow::Error>
+    fn unpack<P1, P2>(
+        dest_dir: P1,
+        archive_file: P2,
+        mp: &MultiProgress,
+    ) -> Result<(), anyhow::Error>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>,
     {
-        let mut archive = Archive::new(GzDecoder::new(File::open(archive_file)?));
+        let workers = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .clamp(1, MAX_WORKERS);
+        let (tx, rx) = mpsc::sync_channel::<FileTask>(workers * QUEUE_DEPTH_PER_WORKER);
+        let rx = Arc::new(Mutex::new(rx));
+        let aborted = AtomicBool::new(false);
+
+        thread::scope(|s| {
+            let handles = (0..workers)
+                .map(|_| {
+                    let rx = Arc::clone(&rx);
+                    let aborted = &aborted;
+                    s.spawn(move || {
+                        loop {
+                            // hold the lock only for receiving.
+                            let task = rx.lock().map_err(|_| anyhow!("queue poisoned"))?.recv();
+                            let Ok(task) = task else {
+                                return Ok(()); // all task dispatched
+                            };
+                            if let Err(e) = Self::write_file(task) {
+                                aborted.store(true, Ordering::Relaxed);
+                                return Err(e);
+                            }
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            drop(rx);
+
+            let dispatched =
+                Self::dispatch(dest_dir.as_ref(), archive_file.as_ref(), tx, &aborted, mp);
+            // worker error take precedence, because dispatch fails once workers are gone.
+            for handle in handles {
+                handle
+                    .join()
+                    .map_err(|_| anyhow!("unpack worker panicked"))??;
+            }
+            dispatched
+        })
+    }
+}
+
+impl Tgz {
+    /// decompress the archive, create directories and dispatch regular files to workers.
+    fn dispatch(
+        dest_dir: &Path,
+        archive_file: &Path,
+        tx: mpsc::SyncSender<FileTask>,
+        aborted: &AtomicBool,
+        mp: &MultiProgress,
+    ) -> Result<(), anyhow::Error> {
+        let mut created_dirs = HashSet::new();
+        let mut create_dir_all = |dir: &Path| -> Result<(), anyhow::Error> {
+            if !created_dirs.contains(dir) {
+                fs::create_dir_all(dir)?;
+                created_dirs.insert(dir.to_path_buf());
+            }
+            Ok(())
+        };
+
+        // progress of the unpacked bytes, the number of entries is unknown up front.
+        let mut file = File::open(archive_file)?;
+        let total = gzip_isize(&mut file).unwrap_or_default();
+        file.rewind()?;
+        let progress = Progress::bytes(mp, total, format!("Unpacking {}", archive_file.display()));
+        let mut archive = Archive::new(progress.wrap_read(GzDecoder::new(file)));
         for entry in archive.entries()? {
+            if aborted.load(Ordering::Relaxed) {
+                break;
+            }
             let mut entry = entry?;
             let path = entry.path()?;
-
-            if let Some(path_str) = path.to_str()
-                && let Some(relative_path) = path_str.strip_prefix("go/")
-            {
-                let dest_file = dest_dir.as_ref().join(relative_path);
-                let parent = dest_file.parent().ok_or(anyhow!("No parent path found"))?;
-                if !parent.exists() {
-                    fs::create_dir_all(parent)?;
+            let Some(relative_path) = path.to_str().and_then(|v| v.strip_prefix("go/")) else {
+                continue;
+            };
+            let dest_file = dest_dir.join(relative_path);
+            let entry_type = entry.header().entry_type();
+            if entry_type.is_dir() {
+                create_dir_all(&dest_file)?;
+                continue;
+            }
+            let parent = dest_file.parent().ok_or(anyhow!("No parent path found"))?;
+            create_dir_all(parent)?;
+            if matches!(entry_type, EntryType::Regular | EntryType::Continuous) {
+                let mut data = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut data)?;
+                let task = FileTask {
+                    path: dest_file,
+                    data,
+                    mode: entry.header().mode().ok(),
+                    mtime: entry.header().mtime().ok(),
+                };
+                if tx.send(task).is_err() {
+                    break; // all workers are gone, the error is reported by them.
                 }
+            } else {
+                // symlink and others are rare, unpack them in place.
                 entry.unpack(dest_file)?;
             }
         }
+        // 读完tar结尾的填充, 进度到100%, 同时校验gzip的CRC
+        if !aborted.load(Ordering::Relaxed) {
+            io::copy(&mut archive.into_inner(), &mut io::sink())?;
+        }
+        progress.finish();
+        Ok(())
+    }
+
+    fn write_file(task: FileTask) -> Result<(), anyhow::Error> {
+        let mut file = File::create(&task.path)?;
+        file.write_all(&task.data)?;
+        if let Some(mtime) = task.mtime {
+            file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(mtime))?;
+        }
+        #[cfg(unix)]
+        if let Some(mode) = task.mode {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(mode & 0o777))?;
+        }
+        #[cfg(not(unix))]
+        let _ = task.mode;
+        Ok(())
+    }
+}
+
+/// the uncompressed size(modulo 2^32) in the gzip trailer.
+fn gzip_isize(file: &mut File) -> Result<u64, anyhow::Error> {
+    let mut isize = [0; 4];
+    file.seek(SeekFrom::End(-4))?;
+    file.read_exact(&mut isize)?;
+    Ok(u32::from_le_bytes(isize).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::path::Path;
+
+    use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+    use indicatif::MultiProgress;
+    use tar::{Builder, EntryType, Header};
+
+    use super::{Tgz, gzip_isize};
+    use crate::archived::Unpacker;
+
+    /// build a synthetic go archive with `dirs` * `files_per_dir` files.
+    fn build_archive(
+        archive_file: &Path,
+        dirs: usize,
+        files_per_dir: usize,
+    ) -> Result<(), anyhow::Error> {
+        let encoder = GzEncoder::new(File::create(archive_file)?, Compression::fast());
+        let mut builder = Builder::new(encoder);
+        let append_dir = |builder: &mut Builder<_>, path: &str| {
+            let mut header = Header::new_gnu();
+            header.set_entry_type(EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_size(0);
+            header.set_cksum();
+            builder.append_data(&mut header, path, std::io::empty())
+        };
+        append_dir(&mut builder, "go/")?;
+        append_dir(&mut builder, "go/bin/")?;
+        for d in 0..dirs {
+            append_dir(&mut builder, &format!("go/src/pkg{d}/"))?;
+            for f in 0..files_per_dir {
+                let content = format!("package pkg{d}\n\n// file {f}\n").repeat(16);
+                let mut header = Header::new_gnu();
+                header.set_mode(0o644);
+                header.set_size(content.len() as u64);
+                header.set_cksum();
+                builder.append_data(
+                    &mut header,
+                    format!("go/src/pkg{d}/file{f}.go"),
+                    content.as_bytes(),
+                )?;
+            }
+        }
+        let mut header = Header::new_gnu();
+        header.set_mode(0o755);
+        header.set_size(4);
+        header.set_cksum();
+        builder.append_data(&mut header, "go/bin/go", &b"\x7fELF"[..])?;
+        // outside `go/` is ignored.
+        let mut header = Header::new_gnu();
+        header.set_mode(0o644);
+        header.set_size(5);
+        header.set_cksum();
+        builder.append_data(&mut header, "other/README", &b"hello"[..])?;
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_unpack() -> Result<(), anyhow::Error> {
+        let tmp_dir = tempfile::tempdir()?;
+        let archive_file = tmp_dir.path().join("go.tar.gz");
+        build_archive(&archive_file, 10, 20)?;
+
+        let mut unpacked = Vec::new();
+        GzDecoder::new(File::open(&archive_file)?).read_to_end(&mut unpacked)?;
+        assert_eq!(
+            gzip_isize(&mut File::open(&archive_file)?)?,
+            unpacked.len() as u64
+        );
+
+        let dest_dir = tmp_dir.path().join("go1.21.5");
+        Tgz::unpack(&dest_dir, &archive_file, &MultiProgress::new())?;
+
+        assert_eq!(fs::read_dir(dest_dir.join("src"))?.count(), 10);
+        assert_eq!(fs::read_dir(dest_dir.join("src/pkg9"))?.count(), 20);
+        assert_eq!(
+            fs::read_to_string(dest_dir.join("src/pkg3/file7.go"))?,
+            "package pkg3\n\n// file 7\n".repeat(16)
+        );
+        assert!(!dest_dir.join("README").exists());
+        assert!(!tmp_dir.path().join("other").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dest_dir.join("bin/go"))?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
         Ok(())
     }
 }
@@ -2,28 +2,50 @@ use std::fs;
 use std::{fs::File, io, path::Path};
 
 use anyhow::anyhow;
+use indicatif::MultiProgress;
 use zip::ZipArchive;
 
 use super::Unpacker;
+use crate::progress::Progress;
+
+/// root directory prefix of the module zip, `golang.org/toolchain@v0.0.1-go1.21.0.linux-amd64/`.
+const TOOLCHAIN_MODULE_ROOT: &str = "golang.org/toolchain@";
 
 /// archive *.zip
 pub(crate) struct Zip;
 
 impl Unpacker for Zip {
-    fn unpack<P1, P2>(dest_dir: P1, archive_file: P2) -> Result<(), anyhow::Error>
+    fn unpack<P1, P2>(
+        dest_dir: P1,
+        archive_file: P2,
+        mp: &MultiProgress,
+    ) -> Result<(), anyhow::Error>
     where
         P1: AsRef<Path>,
         P2: AsRef<Path>,
     {
-        let mut archive = ZipArchive::new(File::open(archive_file)?)?;
+        let mut archive = ZipArchive::new(File::open(&archive_file)?)?;
+        let progress = Progress::entries(
+            mp,
+            archive.len() as u64,
+            format!("Unpacking {}", archive_file.as_ref().display()),
+        );
         for i in 0..archive.len() {
+            progress.inc(1);
             let mut file = archive.by_index(i)?;
             let path = file.mangled_name();
 
             if let Some(path_str) = path.to_str() {
-                // 标准化路径分隔符并检查是否在 go/ 目录下
+                // 标准化路径分隔符并检查是否在 go/ 目录下,
+                // 或GOPROXY模块zip的 golang.org/toolchain@v0.0.1-go1.21.0.linux-amd64/ 目录下
                 let normalized_path = path_str.replace('\\', "/");
-                if let Some(relative_path) = normalized_path.strip_prefix("go/") {
+                let relative_path = normalized_path.strip_prefix("go/").or_else(|| {
+                    normalized_path
+                        .strip_prefix(TOOLCHAIN_MODULE_ROOT)
+                        .and_then(|v| v.split_once('/'))
+                        .map(|v| v.1)
+                });
+                if let Some(relative_path) = relative_path {
                     let dest_file = dest_dir.as_ref().join(relative_path);
                     if file.is_dir() {
                         fs::create_dir_all(&dest_file)?;
@@ -34,11 +56,27 @@ impl Unpacker for Zip {
                         fs::create_dir_all(parent)?;
                     }
 
-                    let mut output_file = File::create(dest_file)?;
+                    let mut output_file = File::create(&dest_file)?;
                     io::copy(&mut file, &mut output_file)?;
+                    // 模块zip的权限不可信, 与go命令一致, bin/ 和 pkg/tool/ 下的文件可执行
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::fs::PermissionsExt;
+                        let mode = if normalized_path.starts_with(TOOLCHAIN_MODULE_ROOT) {
+                            (relative_path.starts_with("bin/")
+                                || relative_path.starts_with("pkg/tool/"))
+                            .then_some(0o755)
+                        } else {
+                            file.unix_mode()
+                        };
+                        if let Some(mode) = mode {
+                            fs::set_permissions(&dest_file, fs::Permissions::from_mode(mode))?;
+                        }
+                    }
                 }
             }
         }
+        progress.finish();
         Ok(())
     }
 }
@@ -0,0 +1,830 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{self, Display};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use indicatif::MultiProgress;
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::dir::Dir;
+use crate::progress::Progress;
+use crate::registries::goproxy;
+use crate::registries::registry::parse_archive_filename;
+use crate::toolchain;
+
+/// marker of `cache/objects`, the legacy flat cache has been migrated.
+const LEGACY_MIGRATED: &str = ".legacy-migrated";
+
+/// how the archive file was verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VerifyMethod {
+    /// not verified, such as download with `--skip-verify`.
+    Unverified,
+    /// verified against the `{archive}.sha256` file of the registry.
+    Sha256File,
+    /// verified against the `h1:` hash of go.sum, for the module zip of a GOPROXY.
+    GoSum,
+}
+
+impl Display for VerifyMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unverified => write!(f, "unverified"),
+            Self::Sha256File => write!(f, "sha256-file"),
+            Self::GoSum => write!(f, "go-sum"),
+        }
+    }
+}
+
+/// result of re-verifying a cached archive file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyStatus {
+    /// matches the sha256 file and/or the index checksum.
+    Verified,
+    /// neither the sha256 file nor the index checksum is available.
+    Unverifiable,
+    /// damaged on disk or mismatch the expected sha256, with the reason.
+    Corrupt(String),
+}
+
+/// metadata sidecar of a cached archive file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheMeta {
+    // sha256 of the archive file, also the key of the cache.
+    pub sha256: String,
+    // archive filename, such as go1.21.5.linux-amd64.tar.gz
+    pub filename: String,
+    // source url, `None` if imported from the legacy cache.
+    pub url: Option<String>,
+    // the registry downloaded from, such as `goproxy+https://proxy.golang.org`, `None` if unknown.
+    #[serde(default)]
+    pub registry: Option<String>,
+    // archive file size.
+    pub size: u64,
+    // how the archive file was verified.
+    pub verify: VerifyMethod,
+    // the time the archive file was added.
+    pub created_at: Timestamp,
+    // the time the archive file was last used.
+    pub accessed_at: Timestamp,
+}
+
+/// a cached archive file, located at `cache/objects/{sha256}/`.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub meta: CacheMeta,
+    dir: Dir,
+}
+
+impl CacheEntry {
+    /// the Go version of the archive file, such as 1.21.5
+    pub fn version(&self) -> Option<&str> {
+        parse_archive_filename(&self.meta.filename).map(|v| v.0)
+    }
+    /// `cache/objects/{sha256}/{filename}`
+    pub fn archive_file(&self) -> PathBuf {
+        self.dir.join(&self.meta.filename)
+    }
+    /// `cache/objects/{sha256}/{filename}.sha256`, the sha256 file from registry.
+    pub fn sha256_file(&self) -> PathBuf {
+        self.dir.join(format!("{}.sha256", self.meta.filename))
+    }
+    /// `cache/objects/{sha256}/{filename}.h1`, the `h1:` hash of go.sum, for the module zip of a GOPROXY.
+    pub fn h1_file(&self) -> PathBuf {
+        self.dir.join(format!("{}.h1", self.meta.filename))
+    }
+    /// the recorded `h1:` hash, `None` if the archive file was not verified by go.sum.
+    pub fn recorded_h1(&self) -> Option<String> {
+        (self.meta.verify == VerifyMethod::GoSum)
+            .then(|| fs::read_to_string(self.h1_file()).ok())
+            .flatten()
+            .map(|v| v.trim().to_owned())
+            .filter(|v| !v.is_empty())
+    }
+    /// `cache/objects/{sha256}/meta.json`
+    fn meta_file(&self) -> PathBuf {
+        self.dir.join("meta.json")
+    }
+    fn write_meta(&self) -> Result<(), anyhow::Error> {
+        let meta_file = self.meta_file();
+        let tmp_file = meta_file.with_extension("json.tmp");
+        serde_json::to_writer_pretty(File::create(&tmp_file)?, &self.meta)?;
+        fs::rename(tmp_file, meta_file)?;
+        Ok(())
+    }
+}
+
+/// content-addressed store of the download archive files.
+///
+/// ```text
+/// cache/
+/// ├── objects/{sha256}/{filename}          archive file
+/// ├── objects/{sha256}/{filename}.sha256   sha256 file from registry, if verified
+/// ├── objects/{sha256}/{filename}.h1       h1 hash of go.sum, if verified by a GOPROXY
+/// ├── objects/{sha256}/meta.json           metadata sidecar
+/// ├── objects/.legacy-migrated             marker, the legacy flat cache has been migrated
+/// └── tmp/                                 in-flight downloads
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArchiveCache {
+    goup_home: Dir,
+}
+
+impl ArchiveCache {
+    /// open the cache of `GOUP_HOME`, migrate the legacy flat cache if any.
+    pub fn open() -> Result<Self, anyhow::Error> {
+        Self::with_home(Dir::goup_home()?)
+    }
+
+    fn with_home(goup_home: Dir) -> Result<Self, anyhow::Error> {
+        let cache = Self { goup_home };
+        cache.migrate_legacy()?;
+        Ok(cache)
+    }
+
+    /// list the cached archive files, sorted by filename.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, anyhow::Error> {
+        let objects = self.goup_home.cache_objects();
+        if !objects.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for dir in objects.read_dir()? {
+            let dir = dir?;
+            if !dir.path().is_dir() {
+                continue;
+            }
+            let sha256 = dir.file_name();
+            if let Some(entry) = self.get(&sha256.to_string_lossy()) {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by(|a, b| a.meta.filename.cmp(&b.meta.filename));
+        Ok(entries)
+    }
+
+    /// get the cached archive file by sha256.
+    pub fn get(&self, sha256: &str) -> Option<CacheEntry> {
+        if !is_sha256(sha256) {
+            return None;
+        }
+        let dir = self.goup_home.cache_object(sha256);
+        let meta = File::open(dir.join("meta.json")).ok()?;
+        let meta: CacheMeta = serde_json::from_reader(meta)
+            .inspect_err(|e| log::warn!("Invalid cache metadata {}: {e}", dir.display()))
+            .ok()?;
+        let entry = CacheEntry { meta, dir };
+        entry.archive_file().exists().then_some(entry)
+    }
+
+    /// find the latest used archive file downloaded from the url.
+    pub fn find_by_url(&self, url: &str) -> Result<Option<CacheEntry>, anyhow::Error> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|v| v.meta.url.as_deref() == Some(url))
+            .max_by_key(|v| v.meta.accessed_at))
+    }
+
+    /// a temporary file path for downloading.
+    pub fn tmp_file(&self, filename: &str) -> Result<PathBuf, anyhow::Error> {
+        let tmp = self.goup_home.cache_tmp();
+        fs::create_dir_all(&tmp)?;
+        Ok(tmp.join(format!("{filename}.part")))
+    }
+
+    /// move the file into the cache, keyed by its sha256.
+    pub fn insert<P: AsRef<Path>>(
+        &self,
+        file: P,
+        sha256: &str,
+        filename: &str,
+        url: Option<&str>,
+        registry: Option<&str>,
+        verify: VerifyMethod,
+    ) -> Result<CacheEntry, anyhow::Error> {
+        if !is_sha256(sha256) {
+            return Err(anyhow!("invalid SHA-256 '{sha256}' of {filename}"));
+        }
+        let dir = self.goup_home.cache_object(sha256);
+        fs::create_dir_all(&dir)?;
+        let now = Timestamp::now();
+        let entry = CacheEntry {
+            meta: CacheMeta {
+                sha256: sha256.to_owned(),
+                filename: filename.to_owned(),
+                url: url.map(ToOwned::to_owned),
+                registry: registry.map(ToOwned::to_owned),
+                size: file.as_ref().metadata()?.len(),
+                verify,
+                created_at: now,
+                accessed_at: now,
+            },
+            dir,
+        };
+        fs::rename(file, entry.archive_file())?;
+        entry.write_meta()?;
+        Ok(entry)
+    }
+
+    /// record the sha256 returned by the registry, the sha256 file or the index checksum,
+    /// mark the archive file verified.
+    pub fn set_verified(&self, entry: &mut CacheEntry, sha256: &str) -> Result<(), anyhow::Error> {
+        self.set_verified_by(entry, VerifyMethod::Sha256File, sha256)
+    }
+
+    /// record the checksum returned by the registry, the sha256 or the `h1:` hash of go.sum,
+    /// mark the archive file verified by the method.
+    pub fn set_verified_by(
+        &self,
+        entry: &mut CacheEntry,
+        method: VerifyMethod,
+        checksum: &str,
+    ) -> Result<(), anyhow::Error> {
+        match method {
+            VerifyMethod::Unverified => return Err(anyhow!("no checksum to record")),
+            VerifyMethod::Sha256File => fs::write(entry.sha256_file(), checksum)?,
+            VerifyMethod::GoSum => fs::write(entry.h1_file(), checksum)?,
+        }
+        if entry.meta.verify != method {
+            entry.meta.verify = method;
+            entry.write_meta()?;
+        }
+        Ok(())
+    }
+
+    /// recompute the sha256 of the archive file, compare it against the recorded sha256 file
+    /// and the checksum of the index if any, mark it verified if it matches.
+    pub fn verify(
+        &self,
+        entry: &mut CacheEntry,
+        index_sha256: Option<&str>,
+        mp: &MultiProgress,
+    ) -> Result<VerifyStatus, anyhow::Error> {
+        let sha256 = match compute_file_sha256(entry.archive_file(), mp) {
+            Ok(sha256) => sha256,
+            Err(e) => return Ok(VerifyStatus::Corrupt(format!("read failure: {e}"))),
+        };
+        if sha256 != entry.meta.sha256 {
+            return Ok(VerifyStatus::Corrupt(format!(
+                "changed on disk, got SHA-256 of {sha256}"
+            )));
+        }
+        let recorded = fs::read_to_string(entry.sha256_file())
+            .ok()
+            .map(|v| v.trim().to_owned());
+        if let Some(ref recorded) = recorded
+            && *recorded != sha256
+        {
+            return Ok(VerifyStatus::Corrupt(format!(
+                "does not have SHA-256 of {recorded} in sha256 file"
+            )));
+        }
+        if let Some(index_sha256) = index_sha256
+            && index_sha256 != sha256
+        {
+            return Ok(VerifyStatus::Corrupt(format!(
+                "does not have SHA-256 of {index_sha256} in index"
+            )));
+        }
+        let h1 = entry.recorded_h1();
+        if let Some(ref h1) = h1 {
+            match goproxy::hash_zip(entry.archive_file()) {
+                Ok(got) if got == *h1 => {}
+                Ok(got) => {
+                    return Ok(VerifyStatus::Corrupt(format!(
+                        "does not have h1 hash of {h1}, got {got}"
+                    )));
+                }
+                Err(e) => return Ok(VerifyStatus::Corrupt(format!("read failure: {e}"))),
+            }
+        }
+        if recorded.is_none() && index_sha256.is_none() && h1.is_none() {
+            return Ok(VerifyStatus::Unverifiable);
+        }
+        if entry.meta.verify == VerifyMethod::Unverified
+            && let Some(expect) = index_sha256.or(recorded.as_deref())
+        {
+            self.set_verified(entry, expect)?;
+        }
+        Ok(VerifyStatus::Verified)
+    }
+
+    /// update the last used time.
+    pub fn touch(&self, entry: &mut CacheEntry) -> Result<(), anyhow::Error> {
+        entry.meta.accessed_at = Timestamp::now();
+        entry.write_meta()
+    }
+
+    /// remove the cached archive file and its sidecars.
+    pub fn remove(&self, entry: &CacheEntry) -> Result<(), anyhow::Error> {
+        // 只删除对象目录, 防止删除对象存储之外的路径
+        if !is_sha256(&entry.meta.sha256)
+            || entry.dir != self.goup_home.cache_object(&entry.meta.sha256)
+        {
+            return Err(anyhow!(
+                "invalid cache object {} of SHA-256 '{}'",
+                entry.dir.display(),
+                entry.meta.sha256
+            ));
+        }
+        if entry.dir.exists() {
+            fs::remove_dir_all(&entry.dir)?;
+        }
+        Ok(())
+    }
+
+    /// the orphaned object directories without a valid meta.json, such as a sha256 file whose
+    /// archive file is gone.
+    pub fn orphans(&self) -> Result<Vec<PathBuf>, anyhow::Error> {
+        let objects = self.goup_home.cache_objects();
+        if !objects.exists() {
+            return Ok(Vec::new());
+        }
+        let mut orphans = Vec::new();
+        for dir in objects.read_dir()? {
+            let dir = dir?.path();
+            let sha256 = dir.file_name().unwrap_or_default().to_string_lossy();
+            if dir.is_dir() && self.get(&sha256).is_none() {
+                orphans.push(dir);
+            }
+        }
+        Ok(orphans)
+    }
+
+    /// remove an orphaned object directory returned by `orphans`.
+    pub fn remove_orphan(&self, dir: &Path) -> Result<(), anyhow::Error> {
+        let sha256 = dir.file_name().unwrap_or_default().to_string_lossy();
+        if dir.parent() != Some(self.goup_home.cache_objects().as_path())
+            || self.get(&sha256).is_some()
+        {
+            return Err(anyhow!("{} is not an orphaned object", dir.display()));
+        }
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    /// import the archive files of the legacy flat layout(`cache/{filename}`) into the store,
+    /// only once, skipped after the marker is written.
+    fn migrate_legacy(&self) -> Result<(), anyhow::Error> {
+        let cache = self.goup_home.cache();
+        let marker = self.goup_home.cache_objects().join(LEGACY_MIGRATED);
+        if !cache.exists() || marker.exists() {
+            return Ok(());
+        }
+        let mut files = Vec::new();
+        for v in cache.read_dir()? {
+            let v = v?;
+            if v.path().is_file() {
+                files.push(v.file_name().to_string_lossy().to_string());
+            }
+        }
+        let mp = MultiProgress::new();
+        for filename in &files {
+            if filename.ends_with(".sha256") {
+                continue;
+            }
+            // 单个文件迁移失败时跳过, 不影响缓存的使用
+            if let Err(e) = self.migrate_legacy_file(filename, &mp) {
+                log::warn!("Skip migrating cache file {filename}: {e}");
+            }
+        }
+        // the archive file has gone, remove the orphaned sha256 file.
+        for filename in files.iter().filter(|v| v.ends_with(".sha256")) {
+            let sha256_file = self.goup_home.cache_file(filename);
+            let archive_file = sha256_file.with_extension("");
+            if !archive_file.exists()
+                && let Err(e) = fs::remove_file(&sha256_file)
+            {
+                log::warn!("Remove {} failed: {e}", sha256_file.display());
+            }
+        }
+        fs::create_dir_all(self.goup_home.cache_objects())?;
+        fs::write(marker, "")?;
+        Ok(())
+    }
+
+    /// import an archive file of the legacy flat layout, with its sha256 file if any.
+    fn migrate_legacy_file(&self, filename: &str, mp: &MultiProgress) -> Result<(), anyhow::Error> {
+        let archive_file = self.goup_home.cache_file(filename);
+        let sha256_file = self.goup_home.cache_file(format!("{filename}.sha256"));
+        log::info!("Migrating cache file {}", archive_file.display());
+        let sha256 = compute_file_sha256(&archive_file, mp)?;
+        let expect_sha256 = fs::read_to_string(&sha256_file)
+            .ok()
+            .map(|v| v.trim().to_owned())
+            .filter(|v| *v == sha256);
+        let mut entry = if let Some(entry) = self.get(&sha256) {
+            fs::remove_file(&archive_file)?;
+            entry
+        } else {
+            self.insert(
+                &archive_file,
+                &sha256,
+                filename,
+                None,
+                None,
+                VerifyMethod::Unverified,
+            )?
+        };
+        if let Some(ref expect_sha256) = expect_sha256 {
+            self.set_verified(&mut entry, expect_sha256)?;
+        }
+        if sha256_file.exists() {
+            fs::remove_file(&sha256_file)?;
+        }
+        Ok(())
+    }
+}
+
+/// whether the value is a sha256 of exactly 64 lowercase hex characters,
+/// the key of the cache object directory.
+pub fn is_sha256(v: &str) -> bool {
+    v.len() == 64 && v.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// policies of pruning the cache, an archive file is removed if any policy selects it.
+#[derive(Debug, Default)]
+pub struct PrunePolicy {
+    /// remove the archive files not used within the duration.
+    pub older_than: Option<SignedDuration>,
+    /// remove the archive files whose version is not installed.
+    pub installed: Option<HashSet<String>>,
+    /// keep only the latest N patch versions per minor line.
+    pub keep_latest: Option<usize>,
+    /// evict the least recently used archive files until the cache fits the size.
+    pub max_size: Option<u64>,
+}
+
+impl PrunePolicy {
+    /// select the entries to remove.
+    pub fn select<'a>(&self, entries: &'a [CacheEntry], now: Timestamp) -> Vec<&'a CacheEntry> {
+        let mut selected = vec![false; entries.len()];
+
+        if let Some(older_than) = self.older_than {
+            for (i, entry) in entries.iter().enumerate() {
+                selected[i] |= now.duration_since(entry.meta.accessed_at) > older_than;
+            }
+        }
+        if let Some(ref installed) = self.installed {
+            for (i, entry) in entries.iter().enumerate() {
+                selected[i] |= !entry.version().is_some_and(|v| installed.contains(v));
+            }
+        }
+        if let Some(keep_latest) = self.keep_latest {
+            // (major, minor) -> [(version, index)]
+            let mut lines: BTreeMap<_, Vec<_>> = BTreeMap::new();
+            for (i, entry) in entries.iter().enumerate() {
+                if let Some(ver) = entry.version().and_then(|v| toolchain::semantic(v).ok()) {
+                    lines
+                        .entry((ver.major, ver.minor))
+                        .or_default()
+                        .push((ver, i));
+                }
+            }
+            for line in lines.into_values() {
+                let mut versions: Vec<_> = line.iter().map(|v| &v.0).collect();
+                versions.sort_by(|a, b| b.cmp(a));
+                versions.dedup(); // same version from different sources
+                versions.truncate(keep_latest);
+                for (ver, i) in &line {
+                    selected[*i] |= !versions.contains(&ver);
+                }
+            }
+        }
+        if let Some(max_size) = self.max_size {
+            let mut remaining: Vec<_> = (0..entries.len()).filter(|&i| !selected[i]).collect();
+            remaining.sort_by_key(|&i| entries[i].meta.accessed_at);
+            let mut total: u64 = remaining.iter().map(|&i| entries[i].meta.size).sum();
+            for i in remaining {
+                if total <= max_size {
+                    break;
+                }
+                selected[i] = true;
+                total -= entries[i].meta.size;
+            }
+        }
+        entries
+            .iter()
+            .zip(selected)
+            .filter_map(|(entry, selected)| selected.then_some(entry))
+            .collect()
+    }
+}
+
+/// compute_file_sha256 计算文件的sha256
+pub fn compute_file_sha256<P: AsRef<Path>>(
+    path: P,
+    mp: &MultiProgress,
+) -> Result<String, anyhow::Error> {
+    let mut context = Sha256::new();
+    let file = File::open(&path)?;
+    let progress = Progress::bytes(
+        mp,
+        file.metadata()?.len(),
+        format!("Verifying {}", path.as_ref().display()),
+    );
+    let mut file = progress.wrap_read(file);
+    let mut buffer = [0; 64 * 1024]; // 定义一个缓冲区来处理字节流数据
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        context.update(&buffer[..bytes_read]);
+    }
+    progress.finish();
+    Ok(hex::encode(context.finalize()))
+}
+
+/// verify_file_sha256 校验文件sha256是否合法
+pub fn verify_file_sha256<P: AsRef<Path>>(
+    path: P,
+    expect_sha256: &str,
+    mp: &MultiProgress,
+) -> Result<(), anyhow::Error> {
+    let computed_sha256 = compute_file_sha256(&path, mp)?;
+    if computed_sha256 != expect_sha256 {
+        return Err(anyhow!(
+            "{} corrupt? does not have expected SHA-256 of {}",
+            path.as_ref().display(),
+            expect_sha256,
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use indicatif::MultiProgress;
+    use jiff::{SignedDuration, Timestamp};
+
+    use super::{
+        ArchiveCache, CacheEntry, CacheMeta, PrunePolicy, VerifyMethod, VerifyStatus,
+        compute_file_sha256,
+    };
+    use crate::dir::Dir;
+
+    fn entry(version: &str, size: u64, accessed_at: i64) -> CacheEntry {
+        CacheEntry {
+            meta: CacheMeta {
+                sha256: version.to_owned(),
+                filename: format!("go{version}.linux-amd64.tar.gz"),
+                url: None,
+                registry: None,
+                size,
+                verify: VerifyMethod::Unverified,
+                created_at: Timestamp::UNIX_EPOCH,
+                accessed_at: Timestamp::from_second(accessed_at).unwrap(),
+            },
+            dir: Dir::new("/tmp"),
+        }
+    }
+
+    fn versions(selected: Vec<&CacheEntry>) -> Vec<&str> {
+        selected.into_iter().filter_map(|v| v.version()).collect()
+    }
+
+    #[test]
+    fn test_insert_and_find() -> Result<(), anyhow::Error> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let cache = ArchiveCache::with_home(Dir::new(&tmp_home_dir))?;
+        assert!(cache.entries()?.is_empty());
+
+        let url = "https://dl.google.com/go/go1.21.5.linux-amd64.tar.gz";
+        let tmp_file = cache.tmp_file("go1.21.5.linux-amd64.tar.gz")?;
+        fs::write(&tmp_file, "archive")?;
+        let sha256 = compute_file_sha256(&tmp_file, &MultiProgress::new())?;
+        let mut entry = cache.insert(
+            &tmp_file,
+            &sha256,
+            "go1.21.5.linux-amd64.tar.gz",
+            Some(url),
+            Some("https://dl.google.com/go"),
+            VerifyMethod::Unverified,
+        )?;
+        assert!(!tmp_file.exists());
+        assert!(entry.archive_file().exists());
+        assert_eq!(entry.meta.size, 7);
+
+        cache.set_verified(&mut entry, &sha256)?;
+        assert_eq!(fs::read_to_string(entry.sha256_file())?, sha256);
+        assert_eq!(entry.recorded_h1(), None);
+        cache.set_verified_by(&mut entry, VerifyMethod::GoSum, "h1:abc=")?;
+        assert_eq!(entry.recorded_h1().as_deref(), Some("h1:abc="));
+        cache.set_verified(&mut entry, &sha256)?;
+
+        let got = cache.find_by_url(url)?.expect("cached archive file");
+        assert_eq!(got.meta, entry.meta);
+        assert_eq!(got.meta.verify, VerifyMethod::Sha256File);
+        assert!(cache.find_by_url("https://example.com/go")?.is_none());
+
+        cache.remove(&got)?;
+        assert!(cache.get(&sha256).is_none());
+
+        // sha256 from network is the directory name, only 64 lowercase hex characters.
+        for sha256 in [
+            "",
+            "..",
+            "../..",
+            "ABC",
+            &sha256.to_uppercase(),
+            &sha256[1..],
+        ] {
+            assert!(cache.get(sha256).is_none());
+            let tmp_file = cache.tmp_file("go1.21.5.linux-amd64.tar.gz")?;
+            fs::write(&tmp_file, "archive")?;
+            assert!(
+                cache
+                    .insert(
+                        &tmp_file,
+                        sha256,
+                        "go1.21.5.linux-amd64.tar.gz",
+                        Some(url),
+                        None,
+                        VerifyMethod::Unverified
+                    )
+                    .is_err()
+            );
+        }
+        let mut escaped = entry.clone();
+        escaped.meta.sha256 = "..".to_owned();
+        assert!(cache.remove(&escaped).is_err());
+
+        // an object directory without meta.json is orphaned, listed but not removed.
+        let orphan = Dir::new(&tmp_home_dir).cache_object(&sha256);
+        fs::create_dir_all(&orphan)?;
+        fs::write(orphan.join("go1.21.5.linux-amd64.tar.gz.sha256"), &sha256)?;
+        assert_eq!(cache.orphans()?, vec![orphan.to_path_buf()]);
+        assert!(orphan.exists());
+        assert!(cache.remove_orphan(tmp_home_dir.path()).is_err());
+        cache.remove_orphan(&orphan)?;
+        assert!(cache.orphans()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify() -> Result<(), anyhow::Error> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let cache = ArchiveCache::with_home(Dir::new(&tmp_home_dir))?;
+        let mp = MultiProgress::new();
+
+        let tmp_file = cache.tmp_file("go1.21.5.linux-amd64.tar.gz")?;
+        fs::write(&tmp_file, "archive")?;
+        let sha256 = compute_file_sha256(&tmp_file, &mp)?;
+        let mut entry = cache.insert(
+            &tmp_file,
+            &sha256,
+            "go1.21.5.linux-amd64.tar.gz",
+            None,
+            None,
+            VerifyMethod::Unverified,
+        )?;
+
+        assert_eq!(
+            cache.verify(&mut entry, None, &mp)?,
+            VerifyStatus::Unverifiable
+        );
+        assert!(matches!(
+            cache.verify(&mut entry, Some("bad"), &mp)?,
+            VerifyStatus::Corrupt(_)
+        ));
+        // the index checksum matches, mark it verified.
+        assert_eq!(
+            cache.verify(&mut entry, Some(&sha256), &mp)?,
+            VerifyStatus::Verified
+        );
+        assert_eq!(entry.meta.verify, VerifyMethod::Sha256File);
+        assert_eq!(cache.verify(&mut entry, None, &mp)?, VerifyStatus::Verified);
+
+        fs::write(entry.archive_file(), "damaged")?;
+        assert!(matches!(
+            cache.verify(&mut entry, None, &mp)?,
+            VerifyStatus::Corrupt(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_legacy() -> Result<(), anyhow::Error> {
+        let tmp_home_dir = tempfile::tempdir()?;
+        let goup_home = Dir::new(&tmp_home_dir);
+        fs::create_dir_all(goup_home.cache())?;
+        fs::write(
+            goup_home.cache_file("go1.21.5.linux-amd64.tar.gz"),
+            "go1.21.5",
+        )?;
+        fs::write(
+            goup_home.cache_file("go1.21.6.linux-amd64.tar.gz"),
+            "go1.21.6",
+        )?;
+        // orphaned
+        fs::write(
+            goup_home.cache_file("go1.21.7.linux-amd64.tar.gz.sha256"),
+            "",
+        )?;
+        let sha256 = compute_file_sha256(
+            goup_home.cache_file("go1.21.5.linux-amd64.tar.gz"),
+            &MultiProgress::new(),
+        )?;
+        fs::write(
+            goup_home.cache_file("go1.21.5.linux-amd64.tar.gz.sha256"),
+            &sha256,
+        )?;
+        // a bad file is skipped, such as its object directory is blocked.
+        fs::write(
+            goup_home.cache_file("go1.21.9.linux-amd64.tar.gz"),
+            "go1.21.9",
+        )?;
+        let blocked = compute_file_sha256(
+            goup_home.cache_file("go1.21.9.linux-amd64.tar.gz"),
+            &MultiProgress::new(),
+        )?;
+        fs::create_dir_all(goup_home.cache_objects())?;
+        fs::write(goup_home.cache_object(&blocked), "")?;
+
+        let cache = ArchiveCache::with_home(goup_home.clone())?;
+        let entries = cache.entries()?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].meta.filename, "go1.21.5.linux-amd64.tar.gz");
+        assert_eq!(entries[0].meta.sha256, sha256);
+        assert_eq!(entries[0].meta.verify, VerifyMethod::Sha256File);
+        assert_eq!(entries[0].meta.url, None);
+        assert_eq!(entries[1].meta.verify, VerifyMethod::Unverified);
+        assert!(goup_home.cache_file("go1.21.9.linux-amd64.tar.gz").exists());
+        fs::remove_file(goup_home.cache_file("go1.21.9.linux-amd64.tar.gz"))?;
+        assert_eq!(goup_home.cache().read_dir()?.count(), 1); // only objects
+
+        // migrated only once, a legacy file written later is left as is.
+        fs::write(
+            goup_home.cache_file("go1.21.8.linux-amd64.tar.gz"),
+            "go1.21.8",
+        )?;
+        let cache = ArchiveCache::with_home(goup_home.clone())?;
+        assert_eq!(cache.entries()?.len(), 2);
+        assert!(goup_home.cache_file("go1.21.8.linux-amd64.tar.gz").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_policy() {
+        let day = 24 * 3600;
+        let now = Timestamp::from_second(100 * day).unwrap();
+        let entries = vec![
+            entry("1.21.4", 10, 10 * day),
+            entry("1.21.5", 10, 99 * day),
+            entry("1.21.6", 10, 98 * day),
+            entry("1.22.0", 10, 97 * day),
+            entry("1.22.1", 10, 96 * day),
+        ];
+
+        assert!(PrunePolicy::default().select(&entries, now).is_empty());
+        let policy = PrunePolicy {
+            older_than: Some(SignedDuration::from_hours(30 * 24)),
+            ..Default::default()
+        };
+        assert_eq!(versions(policy.select(&entries, now)), vec!["1.21.4"]);
+        let policy = PrunePolicy {
+            installed: Some(["1.21.5".to_owned(), "1.22.1".to_owned()].into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            versions(policy.select(&entries, now)),
+            vec!["1.21.4", "1.21.6", "1.22.0"]
+        );
+        let policy = PrunePolicy {
+            keep_latest: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            versions(policy.select(&entries, now)),
+            vec!["1.21.4", "1.21.5", "1.22.0"]
+        );
+        // evict the least recently used first.
+        let policy = PrunePolicy {
+            max_size: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(
+            versions(policy.select(&entries, now)),
+            vec!["1.21.4", "1.22.1"]
+        );
+        // combined, size of the remaining only.
+        let policy = PrunePolicy {
+            keep_latest: Some(1),
+            max_size: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(
+            versions(policy.select(&entries, now)),
+            vec!["1.21.4", "1.21.5", "1.22.0", "1.22.1"]
+        );
+    }
+}
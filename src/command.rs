@@ -1,4 +1,6 @@
+mod audit;
 mod cache;
+mod changelog;
 mod completion;
 mod default;
 mod env;
@@ -6,17 +8,21 @@ mod init;
 mod install;
 mod list;
 mod oneself;
+mod outdated;
 mod remove;
 mod search;
 mod shell;
-mod utils;
+mod update;
+pub(crate) mod utils;
 
 use clap::CommandFactory;
 use clap::{Parser, Subcommand};
 use env_logger::Env as LoggerEnv;
 use std::io::prelude::Write;
 
+use self::audit::Audit;
 use self::cache::Cache;
+use self::changelog::Changelog;
 use self::completion::Completion;
 use self::default::Default;
 use self::env::Env;
@@ -24,8 +30,12 @@ use self::init::Init;
 use self::install::Install;
 use self::list::List;
 use self::oneself::Oneself;
+use self::outdated::Outdated;
 use self::remove::Remove;
 use self::search::Search;
+use self::update::Update;
+use crate::consts;
+use crate::registries::registry_index::multi_index;
 
 // run command.
 pub(crate) trait Run {
@@ -36,8 +46,10 @@ pub(crate) trait Run {
 #[non_exhaustive] // 表明未来还有其它元素添加
 enum Command {
     /// Install Go with a version
-    #[command(visible_aliases = ["update", "i"])]
+    #[command(visible_aliases = ["i"])]
     Install(Install),
+    /// Update every installed minor line to its latest patch.
+    Update(Update),
     /// List all installed Go
     #[command(visible_aliases = ["ls", "show"])]
     List(List),
@@ -63,6 +75,12 @@ enum Command {
     Oneself(Oneself),
     /// Manage cache archive files.
     Cache(Cache),
+    /// Audit the installed Go versions against the Go vulnerability database.
+    Audit(Audit),
+    /// Show the installed Go versions that are end-of-life or superseded by a newer patch.
+    Outdated(Outdated),
+    /// Show the release notes of a Go version or a range of versions.
+    Changelog(Changelog),
     /// Using a specific Go version in a shell session.
     /// If no version is provided, a prompt will show to select a installed Go version.
     Shell(shell::Shell),
@@ -72,6 +90,7 @@ impl Run for Command {
     fn run(&self) -> Result<(), anyhow::Error> {
         match self {
             Command::Install(cmd) => cmd.run(),
+            Command::Update(cmd) => cmd.run(),
             Command::List(cmd) => cmd.run(),
             Command::Remove(cmd) => cmd.run(),
             Command::Search(cmd) => cmd.run(),
@@ -80,6 +99,9 @@ impl Run for Command {
             Command::Init(cmd) => cmd.run(),
             Command::Env(cmd) => cmd.run(),
             Command::Cache(cmd) => cmd.run(),
+            Command::Audit(cmd) => cmd.run(),
+            Command::Outdated(cmd) => cmd.run(),
+            Command::Changelog(cmd) => cmd.run(),
             Command::Completion(c) => completion::print_completions(c.shell, &mut Cli::command()),
             Command::Shell(c) => c.run(),
         }
@@ -94,11 +116,17 @@ impl Run for Command {
 pub struct Cli {
     #[command(subcommand)]
     command: Command,
+    /// offline mode, resolve versions only from the local index, the installed versions and the download cache.
+    #[arg(long, global = true, env = consts::GOUP_OFFLINE, value_parser = clap::builder::FalseyValueParser::new())]
+    offline: bool,
 }
 
 impl Cli {
     fn run_command(&self) -> Result<(), anyhow::Error> {
-        self.command.run()
+        consts::set_offline(self.offline);
+        let result = self.command.run();
+        multi_index::join_stragglers();
+        result
     }
     pub fn run_main() -> Result<(), anyhow::Error> {
         env_logger::builder()
@@ -0,0 +1,130 @@
+use anyhow::anyhow;
+use clap::Args;
+use owo_colors::OwoColorize;
+use semver::Version as SemVersion;
+
+use super::Run;
+use crate::registries::go_index::GoIndex;
+use crate::version::Version;
+use crate::vulndb::{Osv, VulnDb};
+use crate::{consts, toolchain};
+
+#[derive(Args, Debug, PartialEq)]
+pub struct Audit {
+    /// the Go vulnerability database, a url or a local copy, such as `file:///srv/vulndb`.
+    #[arg(long, default_value = consts::GO_VULNDB, env = consts::GOUP_GO_VULNDB)]
+    vulndb: String,
+}
+
+/// vulnerabilities a toolchain is exposed to.
+struct Report<'a> {
+    version: &'a Version,
+    /// (entry, fixed version), `None` if not fixed yet.
+    vulns: Vec<(&'a Osv, Option<SemVersion>)>,
+}
+
+impl Report<'_> {
+    /// the minimum patch release fixes all the vulnerabilities, `None` if any is not fixed yet.
+    fn fixed_in(&self) -> Option<&SemVersion> {
+        let mut fixed_in = None;
+        for (_, fixed) in &self.vulns {
+            fixed_in = fixed_in.max(Some(fixed.as_ref()?));
+        }
+        fixed_in
+    }
+}
+
+impl Run for Audit {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        let versions: Vec<_> = Version::list_go_version()?
+            .into_iter()
+            .filter_map(|v| toolchain::semantic(&v.version).ok().map(|s| (v, s)))
+            .collect();
+        let Some(min_version) = versions.iter().map(|v| &v.1).min() else {
+            log::info!("No Go is installed by goup, nothing to audit.");
+            return Ok(());
+        };
+        let entries = VulnDb::new(&self.vulndb).go_vulns(min_version)?;
+        let releases = GoIndex::read_all();
+
+        let mut vulnerable = 0;
+        for (version, semver) in &versions {
+            let mut report = Report {
+                version,
+                vulns: entries
+                    .iter()
+                    .filter_map(|v| v.affects(semver).map(|fixed| (v, fixed)))
+                    .collect(),
+            };
+            report.vulns.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+            if !report.vulns.is_empty() {
+                vulnerable += 1;
+            }
+            print_report(&report, &releases);
+        }
+        if vulnerable > 0 {
+            return Err(anyhow!(
+                "{vulnerable} of {} installed Go versions are vulnerable",
+                versions.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn print_report(report: &Report, indexes: &[GoIndex]) {
+    let version = &report.version.version;
+    let name = if report.version.default {
+        format!("{version}(default)")
+    } else {
+        version.to_owned()
+    };
+    if report.vulns.is_empty() {
+        println!("{}: {}", name.green(), "no known vulnerabilities".green());
+        return;
+    }
+    let advice = match report.fixed_in() {
+        Some(fixed) => {
+            // 本地索引中同一次版本的最新补丁版本
+            let latest = indexes
+                .iter()
+                .flat_map(|v| &v.releases)
+                .filter_map(|v| toolchain::semantic(&v.version).ok().map(|s| (s, v)))
+                .filter(|(s, v)| {
+                    v.stable && (s.major, s.minor) == (fixed.major, fixed.minor) && s >= fixed
+                })
+                .max_by(|a, b| a.0.cmp(&b.0))
+                .map(|v| format!(", latest {}", v.1.version))
+                .unwrap_or_default();
+            format!("fixed in {}{latest}", go_version(fixed))
+        }
+        None => "not fixed yet".to_owned(),
+    };
+    println!(
+        "{}: {} vulnerabilities, {advice}",
+        name.red(),
+        report.vulns.len()
+    );
+    for (entry, fixed) in &report.vulns {
+        let cves = entry.cves().collect::<Vec<_>>().join(",");
+        let fixed = fixed
+            .as_ref()
+            .map_or_else(|| "not fixed".to_owned(), go_version);
+        println!(
+            "  {} {} {} {}",
+            entry.id.yellow(),
+            cves.dimmed(),
+            entry.summary,
+            format!("(fixed in {fixed})").dimmed()
+        );
+    }
+}
+
+/// the Go version of the semver, 1.21.0 -> 1.21.0, 1.21.0-rc2 -> 1.21rc2
+fn go_version(ver: &SemVersion) -> String {
+    if ver.pre.is_empty() {
+        format!("{}.{}.{}", ver.major, ver.minor, ver.patch)
+    } else {
+        format!("{}.{}{}", ver.major, ver.minor, ver.pre.as_str())
+    }
+}
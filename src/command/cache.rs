@@ -1,9 +1,21 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+
+use anyhow::anyhow;
 use clap::Args;
 use clap::Subcommand;
-use dialoguer::Confirm;
 use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Select};
+use indicatif::{HumanBytes, MultiProgress};
+use jiff::{Timestamp, tz::TimeZone};
+use owo_colors::OwoColorize;
 
 use super::Run;
+use crate::cache::{ArchiveCache, CacheEntry, PrunePolicy, VerifyStatus};
+use crate::command::utils::{Age, ByteSize, IndexOptions};
+use crate::registries::goproxy::GoProxy;
+use crate::registries::registry::Registry;
 use crate::version::Version;
 
 #[derive(Args, Debug, PartialEq)]
@@ -15,10 +27,14 @@ pub struct Cache {
 
 #[derive(Subcommand, Clone, Debug, PartialEq)]
 enum Command {
-    /// Show download archive file
+    /// Show download archive file and its metadata
     Show(Show),
     /// Clean download archive file
     Clean(Clean),
+    /// Prune download archive file by policies, orphaned objects are always removed.
+    Prune(Prune),
+    /// Re-verify the sha256 of every download archive file
+    Verify(Verify),
 }
 
 #[derive(Args, Clone, Debug, PartialEq)]
@@ -35,15 +51,111 @@ struct Clean {
     no_confirm: bool,
 }
 
+#[derive(Args, Clone, Debug, PartialEq)]
+struct Prune {
+    /// Remove archive files not used within the duration, such as '30d', '12h'.
+    #[arg(long, value_parser = clap::value_parser!(Age))]
+    older_than: Option<Age>,
+    /// Keep only archive files for versions still installed.
+    #[arg(long)]
+    keep_installed: bool,
+    /// Keep only the latest N patch versions per minor line.
+    #[arg(long, value_name = "N")]
+    keep_latest: Option<usize>,
+    /// Evict least recently used archive files until the cache fits the size, such as '2G'.
+    #[arg(long, value_parser = clap::value_parser!(ByteSize))]
+    max_size: Option<ByteSize>,
+    /// Only show what would be removed.
+    #[arg(long)]
+    dry_run: bool,
+    /// Skip interact prompt.
+    #[arg(short, long)]
+    no_confirm: bool,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+struct Verify {
+    /// registry index that is used to fetch the archive checksum.
+    #[command(flatten)]
+    index_options: IndexOptions,
+    /// Only compare against the recorded sha256 file, do not query the registry index.
+    #[arg(long)]
+    no_index: bool,
+    /// Action for corrupt or unverifiable archive files, prompt for each if not set.
+    #[arg(long, value_enum)]
+    fix: Option<Fix>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Fix {
+    /// download again from its source, for the archive file with a known source url.
+    Redownload,
+    /// remove the archive file.
+    Delete,
+    /// keep the archive file as it is.
+    Keep,
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Redownload => write!(f, "redownload"),
+            Self::Delete => write!(f, "delete"),
+            Self::Keep => write!(f, "keep"),
+        }
+    }
+}
+
 impl Run for Cache {
     fn run(&self) -> Result<(), anyhow::Error> {
         match self.command {
             Command::Show(ref arg) => {
-                Version::list_cache(Some(arg.contain_sha256))?
-                    .iter()
-                    .for_each(|v| {
-                        println!("{v}");
-                    });
+                let local_time = |ts: Timestamp| {
+                    ts.to_zoned(TimeZone::system())
+                        .strftime("%F %T")
+                        .to_string()
+                };
+                let mut stdout = io::stdout().lock();
+                for entry in ArchiveCache::open()?.entries()? {
+                    let meta = &entry.meta;
+                    writeln!(stdout, "{}", meta.filename.yellow())?;
+                    writeln!(stdout, "  {:<13}{}", "sha256:", meta.sha256)?;
+                    writeln!(stdout, "  {:<13}{}", "size:", HumanBytes(meta.size))?;
+                    writeln!(
+                        stdout,
+                        "  {:<13}{}",
+                        "source:",
+                        meta.url.as_deref().unwrap_or("unknown")
+                    )?;
+                    writeln!(stdout, "  {:<13}{}", "verify:", meta.verify)?;
+                    writeln!(
+                        stdout,
+                        "  {:<13}{}",
+                        "created:",
+                        local_time(meta.created_at)
+                    )?;
+                    writeln!(
+                        stdout,
+                        "  {:<13}{}",
+                        "accessed:",
+                        local_time(meta.accessed_at)
+                    )?;
+                    writeln!(
+                        stdout,
+                        "  {:<13}{}",
+                        "path:",
+                        entry.archive_file().display()
+                    )?;
+                    if arg.contain_sha256 && entry.sha256_file().exists() {
+                        writeln!(
+                            stdout,
+                            "  {:<13}{}",
+                            "sha256 file:",
+                            entry.sha256_file().display()
+                        )?;
+                    }
+                }
+                stdout.flush()?;
             }
             Command::Clean(ref arg) => {
                 let confirmation = arg.no_confirm
@@ -56,7 +168,224 @@ impl Run for Cache {
                     log::info!("Cancelled");
                 }
             }
+            Command::Prune(ref arg) => arg.run()?,
+            Command::Verify(ref arg) => arg.run()?,
+        }
+        Ok(())
+    }
+}
+
+impl Prune {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        let cache = ArchiveCache::open()?;
+        let orphans = cache.orphans()?;
+        let installed = self
+            .keep_installed
+            .then(|| {
+                Version::list_go_version()
+                    .map(|vers| vers.into_iter().map(|v| v.version).collect::<HashSet<_>>())
+            })
+            .transpose()?;
+        let policy = PrunePolicy {
+            older_than: self.older_than.map(|v| v.0),
+            installed,
+            keep_latest: self.keep_latest,
+            max_size: self.max_size.map(|v| v.0),
+        };
+        let entries = cache.entries()?;
+        let selected = policy.select(&entries, Timestamp::now());
+        if selected.is_empty() && orphans.is_empty() {
+            log::info!("Nothing to prune");
+            return Ok(());
+        }
+        let freed: u64 = selected.iter().map(|v| v.meta.size).sum();
+        for entry in &selected {
+            println!(
+                "{} ({}, last used {})",
+                entry.meta.filename,
+                HumanBytes(entry.meta.size),
+                entry
+                    .meta
+                    .accessed_at
+                    .to_zoned(TimeZone::system())
+                    .strftime("%F %T")
+            );
+        }
+        for orphan in &orphans {
+            println!("{} (orphaned)", orphan.display());
+        }
+        let what = if orphans.is_empty() {
+            format!("{} archive files", selected.len())
+        } else {
+            format!(
+                "{} archive files and {} orphaned objects",
+                selected.len(),
+                orphans.len()
+            )
+        };
+        if self.dry_run {
+            log::info!("Would remove {what}, free {}", HumanBytes(freed));
+            return Ok(());
+        }
+        let confirmation = self.no_confirm
+            || Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!(
+                    "Do you want to remove {what}({})?",
+                    HumanBytes(freed)
+                ))
+                .interact()?;
+        if !confirmation {
+            log::info!("Cancelled");
+            return Ok(());
+        }
+        for entry in &selected {
+            cache.remove(entry)?;
+        }
+        for orphan in &orphans {
+            cache.remove_orphan(orphan)?;
+        }
+        log::info!("Removed {what}, freed {}", HumanBytes(freed));
+        Ok(())
+    }
+}
+
+impl Verify {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        let cache = ArchiveCache::open()?;
+        let entries = cache.entries()?;
+        if entries.is_empty() {
+            log::info!("No cached archive files");
+            return Ok(());
+        }
+        let index_sha256 = if self.no_index {
+            HashMap::new()
+        } else {
+            self.index_options
+                .as_registry_index()
+                .list_upstream_archive_sha256()
+                .unwrap_or_else(|e| {
+                    log::warn!(
+                        "Fetch archive checksum from index failure: {e}, only compare against the sha256 file"
+                    );
+                    HashMap::new()
+                })
+        };
+
+        let mp = MultiProgress::new();
+        let mut verified = 0;
+        let mut problems = Vec::new();
+        for mut entry in entries {
+            let expect = index_sha256.get(&entry.meta.filename).map(String::as_str);
+            match cache.verify(&mut entry, expect, &mp)? {
+                VerifyStatus::Verified => {
+                    verified += 1;
+                    println!("{} {}", entry.meta.filename, "ok".green());
+                }
+                VerifyStatus::Unverifiable => {
+                    println!(
+                        "{} {}: no sha256 file or index checksum",
+                        entry.meta.filename,
+                        "unverifiable".yellow()
+                    );
+                    problems.push((entry, false));
+                }
+                VerifyStatus::Corrupt(reason) => {
+                    println!("{} {}: {reason}", entry.meta.filename, "corrupt".red());
+                    problems.push((entry, true));
+                }
+            }
         }
+        let corrupt = problems.iter().filter(|v| v.1).count();
+        log::info!(
+            "Verified {verified}, unverifiable {}, corrupt {corrupt}",
+            problems.len() - corrupt,
+        );
+
+        let mut failed = 0;
+        for (entry, corrupt) in &problems {
+            let redownloadable = entry.meta.url.is_some();
+            let fix = match self.fix {
+                Some(Fix::Redownload) if !redownloadable => {
+                    log::warn!(
+                        "Cannot redownload {}, its source url is unknown",
+                        entry.meta.filename
+                    );
+                    failed += 1;
+                    continue;
+                }
+                Some(fix) => fix,
+                None => {
+                    let items = if redownloadable {
+                        vec![Fix::Redownload, Fix::Delete, Fix::Keep]
+                    } else {
+                        vec![Fix::Delete, Fix::Keep]
+                    };
+                    let selection = Select::with_theme(&ColorfulTheme::default())
+                        .with_prompt(format!("What to do with {}", entry.meta.filename))
+                        .items(&items)
+                        .default(0)
+                        .interact()?;
+                    items[selection]
+                }
+            };
+            let result = match fix {
+                Fix::Redownload => Self::redownload(
+                    &cache,
+                    entry,
+                    index_sha256.get(&entry.meta.filename),
+                    *corrupt,
+                    &mp,
+                ),
+                Fix::Delete => cache
+                    .remove(entry)
+                    .inspect(|_| log::info!("Removed {}", entry.archive_file().display())),
+                Fix::Keep => {
+                    log::info!("Keep {}", entry.archive_file().display());
+                    Ok(())
+                }
+            };
+            // 单个文件修复失败不影响其余文件
+            if let Err(e) = result {
+                log::error!("Failed to {fix} {}: {e}", entry.meta.filename);
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!("{failed} archive files failed to fix"));
+        }
+        Ok(())
+    }
+
+    /// download the archive file again from the registry it was downloaded from.
+    fn redownload(
+        cache: &ArchiveCache,
+        entry: &CacheEntry,
+        index_sha256: Option<&String>,
+        corrupt: bool,
+        mp: &MultiProgress,
+    ) -> Result<(), anyhow::Error> {
+        let Some(ref url) = entry.meta.url else {
+            return Err(anyhow!("unknown source url"));
+        };
+        // 未记录来源仓库的缓存, 按url推断: GOPROXY的模块zip, 否则为普通仓库
+        let registry = match entry.meta.registry {
+            Some(ref registry) => registry.clone(),
+            None => match GoProxy::split_archive_url(url) {
+                Some((proxy, _)) => format!("goproxy+{proxy}"),
+                None => url
+                    .rsplit_once('/')
+                    .map_or(url.as_str(), |v| v.0)
+                    .to_owned(),
+            },
+        };
+        let fresh = Registry::new(&registry, false, false)?.redownload_archive(
+            cache,
+            entry,
+            index_sha256.map(String::as_str),
+            corrupt,
+            mp,
+        )?;
+        log::info!("Redownloaded {}", fresh.archive_file().display());
         Ok(())
     }
 }
@@ -0,0 +1,81 @@
+use anyhow::anyhow;
+use clap::Args;
+use owo_colors::OwoColorize;
+use semver::Version;
+
+use super::Run;
+use crate::release_notes::{ReleaseHistory, ReleaseNote};
+use crate::{consts, toolchain};
+
+#[derive(Args, Debug, PartialEq)]
+#[command(disable_version_flag = true)]
+pub struct Changelog {
+    /// a version such as '1.22.5', or a range such as '1.22.3..1.22.6' shows the releases after '1.22.3' up to '1.22.6'.
+    version: String,
+    /// base url of the release history page, a mirror or a local copy such as `file:///srv/go.dev`,
+    /// default the host of the official registry index.
+    #[arg(long, env = consts::GOUP_GO_RELEASE_NOTES)]
+    release_notes: Option<String>,
+    /// ignore the cached release history page.
+    #[arg(long)]
+    refresh: bool,
+}
+
+impl Run for Changelog {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        let (from, to) = match self.version.split_once("..") {
+            Some((from, to)) => (Some(semantic(from)?), semantic(to)?),
+            None => (None, semantic(&self.version)?),
+        };
+        if from.as_ref().is_some_and(|from| from >= &to) {
+            return Err(anyhow!("invalid range {}", self.version));
+        }
+        let base = self
+            .release_notes
+            .clone()
+            .unwrap_or_else(consts::go_release_notes);
+        let history = ReleaseHistory::new(&base, self.refresh);
+        let notes = history.release_notes(&to)?;
+        if !notes.iter().any(|v| v.semver == to) {
+            return Err(anyhow!("no release notes of {to} in {}", history.url()));
+        }
+        notes
+            .iter()
+            .filter(|v| match &from {
+                Some(from) => &v.semver > from && v.semver <= to,
+                None => v.semver == to,
+            })
+            .for_each(print_note);
+        Ok(())
+    }
+}
+
+/// the semver of the version, such as `1.22.5` or `go1.22.5`.
+fn semantic(version: &str) -> Result<Version, anyhow::Error> {
+    toolchain::semantic(version.trim().trim_start_matches("go"))
+        .map_err(|_| anyhow!("invalid version '{version}'"))
+}
+
+fn print_note(note: &ReleaseNote) {
+    let date = note
+        .date
+        .map(|v| format!(" (released {v})"))
+        .unwrap_or_default();
+    println!("{}{}", format!("go{}", note.version).green(), date.dimmed());
+    if note.security_fixes.is_empty() && note.bug_fixes.is_empty() {
+        println!("  {}", note.summary);
+    }
+    if !note.security_fixes.is_empty() {
+        println!(
+            "  {} {}",
+            "security fixes:".red(),
+            note.security_fixes.join(", ")
+        );
+    }
+    if !note.bug_fixes.is_empty() {
+        println!("  {} {}", "bug fixes:".yellow(), note.bug_fixes.join(", "));
+    }
+    if let Some(issues) = &note.issues {
+        println!("  {} {}", "fixed issues:".cyan(), issues);
+    }
+}
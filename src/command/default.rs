@@ -3,7 +3,8 @@ use clap::Args;
 use dialoguer::{Select, theme::ColorfulTheme};
 
 use crate::{
-    command::utils::InstallOptions, registries::registry::Registry, toolchain, version::Version,
+    command::utils::{InstallOptions, warn_lifecycle},
+    version::Version,
 };
 
 use super::Run;
@@ -11,7 +12,7 @@ use super::Run;
 #[derive(Args, Debug, PartialEq)]
 #[command(disable_version_flag = true)]
 pub struct Default {
-    /// target go version
+    /// target go version, an installed version, a toolchain name or a semver request such as '~1.21'
     version: Option<String>,
     #[command(flatten)]
     install_options: InstallOptions,
@@ -21,15 +22,7 @@ impl Run for Default {
     fn run(&self) -> Result<(), anyhow::Error> {
         let versions = Version::list_go_version()?;
         let target_version = if let Some(version) = &self.version {
-            if !versions.iter().any(|v| v.version == *version) {
-                let registry = Registry::new(
-                    &self.install_options.registry,
-                    self.install_options.skip_verify,
-                    self.install_options.enable_check_archive_size,
-                );
-                registry.install_go(&toolchain::normalize(version))?
-            }
-            version
+            self.install_options.resolve_version(version, &versions)?
         } else {
             if versions.is_empty() {
                 return Err(anyhow!(
@@ -49,8 +42,9 @@ impl Run for Default {
                 .items(&items)
                 .default(pos)
                 .interact()?;
-            items[selection]
+            items[selection].to_owned()
         };
-        Version::set_go_version(target_version)
+        warn_lifecycle(&self.install_options.index_options, &target_version);
+        Version::set_go_version(&target_version)
     }
 }
@@ -35,6 +35,26 @@ impl Run for Env {
                 consts::go_registry(),
                 "Registry of go archive file",
             ),
+            (
+                consts::GOUP_INDEX_TTL,
+                consts::index_ttl(),
+                "Cache duration of the go version index, default: '1h'",
+            ),
+            (
+                consts::GOUP_GO_VULNDB,
+                consts::go_vulndb(),
+                "Go vulnerability database, url or local copy",
+            ),
+            (
+                consts::GOUP_GO_RELEASE_NOTES,
+                consts::go_release_notes(),
+                "Base url of the release history page",
+            ),
+            (
+                consts::GOUP_PREFER_INSTALLED,
+                consts::prefer_installed(),
+                "Prefer installed go version satisfying the request",
+            ),
             (
                 consts::GOUP_GO_SOURCE_GIT_URL,
                 consts::go_source_git_url(),
@@ -5,14 +5,25 @@ use owo_colors::OwoColorize;
 use which::which;
 
 use super::Run;
+use crate::command::utils::IndexOptions;
+use crate::registries::go_index::{GoIndex, Lifecycle};
 use crate::version::Version;
 
 #[derive(Args, Debug, PartialEq)]
-pub struct List;
+pub struct List {
+    /// show the lifecycle of each version, supported, end-of-life or superseded by a newer patch.
+    #[arg(long)]
+    lifecycle: bool,
+    #[command(flatten)]
+    index_options: IndexOptions,
+}
 
 impl Run for List {
     fn run(&self) -> Result<(), anyhow::Error> {
         let vers = Version::list_go_version()?;
+        if self.lifecycle && !vers.is_empty() {
+            return print_lifecycle(&vers, &self.index_options, false);
+        }
         if vers.is_empty() {
             log::info!(
                 "No Go is installed by goup.{}",
@@ -52,3 +63,50 @@ impl Run for List {
         Ok(())
     }
 }
+
+/// print the lifecycle of the installed versions, only the outdated ones if `outdated_only`.
+pub(super) fn print_lifecycle(
+    vers: &[Version],
+    index_options: &IndexOptions,
+    outdated_only: bool,
+) -> Result<(), anyhow::Error> {
+    let index: GoIndex = index_options
+        .as_registry_index()
+        .list_upstream_releases()?
+        .into();
+    let mut stdout = io::stdout().lock();
+    let mut outdated = 0;
+    for v in vers {
+        let Some(lifecycle) = index.lifecycle(&v.version) else {
+            continue;
+        };
+        let version = if v.default {
+            format!("{}(default)", v.version)
+        } else {
+            v.version.clone()
+        };
+        match lifecycle {
+            Lifecycle::Supported if outdated_only => {}
+            Lifecycle::Supported => {
+                writeln!(stdout, "{:<18}{}", version, lifecycle.green())?;
+            }
+            Lifecycle::Superseded(_) => {
+                outdated += 1;
+                writeln!(stdout, "{:<18}{}", version, lifecycle.yellow())?;
+            }
+            Lifecycle::EndOfLife(_) => {
+                outdated += 1;
+                writeln!(stdout, "{:<18}{}", version, lifecycle.red())?;
+            }
+        }
+    }
+    stdout.flush()?;
+    if outdated_only && outdated == 0 {
+        log::info!(
+            "All installed Go versions are up to date, the supported releases are {} and {}",
+            index.latest,
+            index.secondary
+        );
+    }
+    Ok(())
+}
@@ -12,6 +12,7 @@ use self_update::{backends::github::Update, cargo_crate_version};
 
 use super::Cli;
 use super::Run;
+use crate::consts;
 use crate::version::Version;
 
 #[derive(Args, Debug, PartialEq)]
@@ -45,6 +46,7 @@ impl Run for Oneself {
                     log::warn!("you should use your system package manager to update goup");
                     return Ok(());
                 }
+                consts::ensure_online("updating goup")?;
                 let cmd = Cli::command();
                 let status = Update::configure()
                     .repo_owner("thinkgos")
@@ -0,0 +1,23 @@
+use clap::Args;
+
+use super::Run;
+use super::list::print_lifecycle;
+use crate::command::utils::IndexOptions;
+use crate::version::Version;
+
+#[derive(Args, Debug, PartialEq)]
+pub struct Outdated {
+    #[command(flatten)]
+    index_options: IndexOptions,
+}
+
+impl Run for Outdated {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        let vers = Version::list_go_version()?;
+        if vers.is_empty() {
+            log::info!("No Go is installed by goup.");
+            return Ok(());
+        }
+        print_lifecycle(&vers, &self.index_options, true)
+    }
+}
@@ -4,32 +4,53 @@ use clap::Args;
 use owo_colors::OwoColorize;
 
 use super::Run;
-use crate::registries::registry_index::RegistryIndexType;
-use crate::{consts, toolchain::ToolchainFilter, version::Version};
+use crate::command::utils::IndexOptions;
+use crate::registries::registry::platform;
+use crate::{toolchain::ToolchainFilter, version::Version};
 
 #[derive(Args, Debug, PartialEq)]
 pub struct Search {
     /// a filter, such as 'stable', "unstable", 'beta' or any regex string(1.22.*).
     #[arg(value_parser = clap::value_parser!(ToolchainFilter))]
     filter: Option<ToolchainFilter>,
-    /// registry index that is used to update Go version index.
-    #[arg(long, default_value_t = RegistryIndexType::Official(consts::GO_REGISTRY_INDEX.to_owned()), env = consts::GOUP_GO_REGISTRY_INDEX, value_parser = clap::value_parser!(RegistryIndexType))]
-    registry_index: RegistryIndexType,
+    #[command(flatten)]
+    index_options: IndexOptions,
+    /// only show versions that have an archive file for the current platform,
+    /// the versions whose files are unknown, such as git tags, are hidden too.
+    #[arg(long)]
+    available_only: bool,
 }
 
 impl Run for Search {
     fn run(&self) -> Result<(), anyhow::Error> {
-        let remote_versions = self
-            .registry_index
-            .as_registry_index()
-            .list_upstream_go_versions_filter(self.filter.as_ref())?;
+        let releases = self
+            .index_options
+            .as_revalidated_registry_index()
+            .list_upstream_releases_filter(self.filter.as_ref())?;
+        let (os, arch) = platform();
         let local_versions = Version::list_go_version().unwrap_or_default();
         let local_versions: HashSet<_> = local_versions.iter().map(|v| &v.version).collect();
-        remote_versions.iter().for_each(|version| {
-            if local_versions.contains(&version) {
-                println!("{}{}", version.yellow(), "(installed)".yellow());
+        releases.iter().for_each(|release| {
+            // unknown if the index does not list files, such as git tags.
+            let available = release.has_archive(os, arch);
+            if self.available_only && available != Some(true) {
+                return;
+            }
+            let version = &release.version;
+            let mut note = release
+                .date
+                .map(|v| format!(" {}", v.dimmed()))
+                .unwrap_or_default();
+            if available == Some(false) {
+                note.push_str(&format!(
+                    " {}",
+                    format!("(no {os}/{arch} archive)").dimmed()
+                ));
+            }
+            if local_versions.contains(version) {
+                println!("{}{}{note}", version.yellow(), "(installed)".yellow());
             } else {
-                println!("{version}");
+                println!("{version}{note}");
             }
         });
         Ok(())
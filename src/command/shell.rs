@@ -14,10 +14,10 @@ use dialoguer::{Select, theme::ColorfulTheme};
 use semver::VersionReq;
 
 use crate::{
-    command::utils::{InstallOptions, KeyValuePair},
+    command::utils::{InstallOptions, KeyValuePair, warn_lifecycle},
     consts::GOUP_GO_VERSION,
     dir::Dir,
-    registries::{go_index::GoIndex, registry::Registry},
+    registries::registry::Registry,
     shell::ShellType,
     toolchain,
     version::Version,
@@ -28,7 +28,7 @@ use super::Run;
 #[derive(Args, Debug, PartialEq)]
 #[command(disable_version_flag = true)]
 pub struct Shell {
-    /// target go version
+    /// target go version, an installed version, a toolchain name or a semver request such as '>=1.22'
     version: Option<String>,
     /// custom shell type
     #[arg(short, long)]
@@ -72,6 +72,7 @@ impl Run for Shell {
         let local_versions = Version::list_go_version()?;
         let target_go_version = self.get_target_version(&local_versions)?;
         let target_go_version = toolchain::normalize(&target_go_version);
+        warn_lifecycle(&self.install_options.index_options, &target_go_version);
         let goup_home = Dir::goup_home()?;
         if !goup_home.is_dot_unpacked_success_file_exists(&target_go_version) {
             return Err(anyhow!(
@@ -146,16 +147,10 @@ impl Run for Shell {
 impl Shell {
     fn get_target_version(&self, local_versions: &[Version]) -> Result<String, anyhow::Error> {
         if let Some(version) = &self.version {
-            // 指定了版本号，直接使用该版本
-            if !local_versions.iter().any(|v| v.version == *version) {
-                let registry = Registry::new(
-                    &self.install_options.registry,
-                    self.install_options.skip_verify,
-                    self.install_options.enable_check_archive_size,
-                );
-                registry.install_go(&toolchain::normalize(version))?
-            }
-            return Ok(version.to_owned());
+            // 指定了版本号或版本请求, 优先使用已安装的最高匹配版本
+            return self
+                .install_options
+                .resolve_version(version, local_versions);
         }
         if !self.skip_autodetect
             && let Some(ver) = self.get_mod_file_version(local_versions)
@@ -196,23 +191,35 @@ impl Shell {
             .into_iter()
             .find_map(|filename| Self::parse_go_mod_or_work_file(current_dir.join(filename)))?;
 
-        let version_req = match mod_go_version.chars().filter(|&v| v == '.').count() {
-            0 | 1 => format!("~{mod_go_version}"),
-            _ => format!("={mod_go_version}"),
-        };
+        let version_req = toolchain::version_req(&mod_go_version, false);
         // let version = RegistryIndex::new(&self.install_options.registry_index)
         //     .match_version_req(&version_req)
         //     .ok()?;
-        // 从本地索引中匹配版本号
         let ver_req = VersionReq::parse(&version_req).ok()?;
-        let version = GoIndex::read().and_then(|v| v.match_version(&ver_req))?;
+        // 优先使用已安装的版本
+        if self.install_options.prefer_installed
+            && let Some(version) = Version::match_installed(local_versions, &ver_req)
+        {
+            log::info!(
+                "Use installed Go {} satisfying '{version_req}'",
+                version.version
+            );
+            return Some(version.version.clone());
+        }
+        // 从本地索引中匹配版本号
+        let index = self.install_options.index_options.local_index()?;
+        let release = index.match_release(&ver_req)?;
+        let version = release.version.clone();
         if !local_versions.iter().any(|v| v.version == version) {
-            let registry = Registry::new(
+            Registry::new(
                 &self.install_options.registry,
                 self.install_options.skip_verify,
                 self.install_options.enable_check_archive_size,
-            );
-            registry.install_go(&toolchain::normalize(&version)).ok();
+            )
+            .and_then(|registry| {
+                registry.install_go(&toolchain::normalize(&version), Some(release))
+            })
+            .ok();
         }
         Some(version)
     }
@@ -0,0 +1,326 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use anyhow::anyhow;
+use clap::Args;
+
+use super::Run;
+use crate::command::utils::InstallOptions;
+use crate::registries::registry::Registry;
+use crate::registries::release::Release;
+use crate::toolchain::{self, Toolchain};
+use crate::version::Version;
+
+#[derive(Args, Debug, PartialEq)]
+#[command(disable_version_flag = true)]
+pub struct Update {
+    /// remove the superseded patches of each updated minor line.
+    #[arg(long)]
+    prune: bool,
+    /// only install the latest patches, but do not move the default.
+    #[arg(long)]
+    dry: bool,
+    /// deprecated, `goup update <TOOLCHAIN>` was an alias of `goup install <TOOLCHAIN>`.
+    #[arg(hide = true, value_parser = clap::value_parser!(Toolchain))]
+    toolchain: Option<Toolchain>,
+    /// deprecated, the change list (CL) of `goup update tip <CL>`.
+    #[arg(hide = true)]
+    cl: Option<String>,
+    /// deprecated, the same as `goup install --use-raw-version`.
+    #[arg(long, hide = true, requires = "toolchain")]
+    use_raw_version: bool,
+    /// deprecated, the same as `goup install --caret`.
+    #[arg(long, hide = true, requires = "toolchain")]
+    caret: bool,
+    #[command(flatten)]
+    install_options: InstallOptions,
+}
+
+/// an installed minor line, such as 1.21.x
+struct Line<'a> {
+    /// the installed versions, sorted.
+    installed: Vec<&'a Version>,
+    /// the latest stable patch of the registry index.
+    latest: Option<&'a Release>,
+}
+
+impl Run for Update {
+    fn run(&self) -> Result<(), anyhow::Error> {
+        if let Some(ref toolchain) = self.toolchain {
+            log::warn!(
+                "`goup update <TOOLCHAIN>` is deprecated, use `goup install <TOOLCHAIN>` instead"
+            );
+            return self.install(toolchain);
+        }
+        let local_versions = Version::list_go_version()?;
+        // 弃用过渡期: 未安装任何Go时, 仍如旧的`goup update`一样安装稳定版本
+        if local_versions.is_empty() {
+            log::warn!(
+                "No Go is installed by goup, installing the stable version. `goup update` without a toolchain will only update the installed minor lines in a future release, use `goup install` instead"
+            );
+            return self.install(&Toolchain::Stable);
+        }
+        let opt = &self.install_options;
+        let releases = opt
+            .index_options
+            .as_registry_index()
+            .list_upstream_releases()?;
+        let registry = Registry::new(
+            &opt.registry,
+            opt.skip_verify,
+            opt.enable_check_archive_size,
+        )?;
+
+        let lines = plan(&local_versions, &releases);
+        if lines.is_empty() {
+            log::info!("No minor line of the installed Go to update.");
+            return Ok(());
+        }
+
+        let mut rows = vec![(
+            "Minor".to_owned(),
+            "Installed".to_owned(),
+            "Latest".to_owned(),
+            "Result".to_owned(),
+        )];
+        let mut failed = 0;
+        for ((major, minor), line) in &lines {
+            let installed = line
+                .installed
+                .iter()
+                .map(|v| v.version.as_str())
+                .collect::<Vec<_>>();
+            let Some(latest) = line.latest else {
+                rows.push((
+                    format!("{major}.{minor}"),
+                    installed.join(", "),
+                    "-".to_owned(),
+                    "not in registry index".to_owned(),
+                ));
+                continue;
+            };
+            let result = self.update_line(&registry, line, latest);
+            if result.is_err() {
+                failed += 1;
+            }
+            rows.push((
+                format!("{major}.{minor}"),
+                installed.join(", "),
+                latest.version.clone(),
+                result.unwrap_or_else(|e| format!("failed: {e}")),
+            ));
+        }
+        print_table(&rows)?;
+        if failed > 0 {
+            return Err(anyhow!("{failed} minor lines failed to update"));
+        }
+        Ok(())
+    }
+}
+
+impl Update {
+    /// the deprecated `goup update <TOOLCHAIN>`, the same as `goup install <TOOLCHAIN>`.
+    fn install(&self, toolchain: &Toolchain) -> Result<(), anyhow::Error> {
+        let version = self.install_options.install_toolchain(
+            toolchain,
+            self.cl.as_deref(),
+            self.use_raw_version,
+            self.caret,
+        )?;
+        if !self.dry {
+            Version::set_go_version(&version)?;
+        }
+        Ok(())
+    }
+
+    /// install the latest patch of the line, move the default, and prune if asked.
+    fn update_line(
+        &self,
+        registry: &Registry,
+        line: &Line,
+        latest: &Release,
+    ) -> Result<String, anyhow::Error> {
+        let mut result = Vec::new();
+        if line.installed.iter().any(|v| v.version == latest.version) {
+            result.push("up to date".to_owned());
+        } else {
+            registry.install_go(&toolchain::normalize(&latest.version), Some(latest))?;
+            result.push("updated".to_owned());
+        }
+        if !self.dry
+            && line
+                .installed
+                .iter()
+                .any(|v| v.default && v.version != latest.version)
+        {
+            Version::set_go_version(&latest.version)?;
+            result.push("default moved".to_owned());
+        }
+        if self.prune {
+            let superseded = line
+                .installed
+                .iter()
+                .filter(|v| v.version != latest.version)
+                .map(|v| v.version.as_str())
+                .collect::<Vec<_>>();
+            if !superseded.is_empty() {
+                Version::remove_go_versions(&superseded)?;
+                result.push(format!("pruned {}", superseded.join(", ")));
+            }
+        }
+        Ok(result.join(", "))
+    }
+}
+
+/// group the installed versions by minor line, with the latest stable patch of the line.
+fn plan<'a>(installed: &'a [Version], releases: &'a [Release]) -> BTreeMap<(u64, u64), Line<'a>> {
+    let mut lines: BTreeMap<(u64, u64), Line> = BTreeMap::new();
+    for v in installed {
+        // gotip 等非语义化版本不参与更新
+        let Ok(semver) = toolchain::semantic(&v.version) else {
+            continue;
+        };
+        let minor = (semver.major, semver.minor);
+        lines
+            .entry(minor)
+            .or_insert_with(|| Line {
+                installed: Vec::new(),
+                latest: releases.iter().rev().filter(|v| v.stable).find(|v| {
+                    toolchain::semantic(&v.version).is_ok_and(|s| (s.major, s.minor) == minor)
+                }),
+            })
+            .installed
+            .push(v);
+    }
+    // 按语义化版本排序, 而不是按字符串排序
+    for line in lines.values_mut() {
+        line.installed
+            .sort_by_cached_key(|v| toolchain::semantic(&v.version).ok());
+    }
+    lines
+}
+
+/// print the summary table, the first row is the header.
+fn print_table(rows: &[(String, String, String, String)]) -> Result<(), anyhow::Error> {
+    let width = |f: fn(&(String, String, String, String)) -> &String| {
+        rows.iter().map(|v| f(v).len()).max().unwrap_or_default()
+    };
+    let widths = [
+        width(|v| &v.0),
+        width(|v| &v.1),
+        width(|v| &v.2),
+        width(|v| &v.3),
+    ];
+    let mut stdout = io::stdout().lock();
+    let write_separator = |write: &mut dyn Write| {
+        writeln!(
+            write,
+            "+-{}-+-{}-+-{}-+-{}-+",
+            "-".repeat(widths[0]),
+            "-".repeat(widths[1]),
+            "-".repeat(widths[2]),
+            "-".repeat(widths[3])
+        )
+    };
+    write_separator(&mut stdout)?;
+    for (i, (minor, installed, latest, result)) in rows.iter().enumerate() {
+        writeln!(
+            stdout,
+            "| {:<w0$} | {:<w1$} | {:<w2$} | {:<w3$} |",
+            minor,
+            installed,
+            latest,
+            result,
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+        )?;
+        if i == 0 {
+            write_separator(&mut stdout)?;
+        }
+    }
+    write_separator(&mut stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::{Update, plan};
+    use crate::registries::release::Release;
+    use crate::toolchain::Toolchain;
+    use crate::version::Version;
+
+    #[derive(Parser, Debug)]
+    struct Cli {
+        #[command(flatten)]
+        update: Update,
+    }
+
+    #[test]
+    fn test_parse_deprecated_toolchain() {
+        let update = Cli::try_parse_from(["update", "1.21", "--dry"])
+            .expect("`goup update <TOOLCHAIN> --dry` still parses")
+            .update;
+        assert_eq!(update.toolchain, Some("1.21".parse::<Toolchain>().unwrap()));
+        assert!(update.dry);
+
+        let update = Cli::try_parse_from(["update", "tip", "12345"])
+            .unwrap()
+            .update;
+        assert_eq!(update.cl.as_deref(), Some("12345"));
+        assert!(
+            Cli::try_parse_from(["update", "1.21.4", "--use-raw-version"])
+                .unwrap()
+                .update
+                .use_raw_version
+        );
+        // the install flags need the toolchain.
+        assert!(Cli::try_parse_from(["update", "--use-raw-version"]).is_err());
+        let update = Cli::try_parse_from(["update", "--prune"]).unwrap().update;
+        assert_eq!(update.toolchain, None);
+        assert!(update.prune);
+    }
+
+    #[test]
+    fn test_plan() {
+        let installed =
+            ["1.21.0", "1.21.10", "1.21.3", "1.22.1", "1.23rc1", "tip"].map(|v| Version {
+                version: v.to_owned(),
+                default: v == "1.21.0",
+                session: false,
+            });
+        let releases = ["1.21.0", "1.21.3", "1.21.13", "1.22.1", "1.23rc1", "1.24.0"]
+            .map(Release::new)
+            .to_vec();
+        let lines = plan(&installed, &releases);
+        let summary = lines
+            .iter()
+            .map(|(minor, line)| {
+                (
+                    *minor,
+                    line.installed
+                        .iter()
+                        .map(|v| v.version.as_str())
+                        .collect::<Vec<_>>(),
+                    line.latest.map(|v| v.version.as_str()),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    (1, 21),
+                    vec!["1.21.0", "1.21.3", "1.21.10"],
+                    Some("1.21.13")
+                ),
+                ((1, 22), vec!["1.22.1"], Some("1.22.1")),
+                ((1, 23), vec!["1.23rc1"], None),
+            ]
+        );
+    }
+}
@@ -1,8 +1,19 @@
 use std::str::FromStr;
 
+use anyhow::anyhow;
 use clap::Args;
+use jiff::{SignedDuration, Span, SpanRelativeTo};
+use semver::VersionReq;
 
-use crate::{consts, registries::registry_index::RegistryIndexType};
+use crate::consts;
+use crate::registries::go_index::{GoIndex, Lifecycle};
+use crate::registries::registry::{NightlyRegistry, Registry};
+use crate::registries::registry_index::cached_index::CachedIndex;
+use crate::registries::registry_index::multi_index::MultiIndex;
+use crate::registries::registry_index::{RegistryIndex, RegistryIndexType, split_registry_indexes};
+use crate::registries::release::Release;
+use crate::toolchain::{self, Toolchain, ToolchainFilter};
+use crate::version::Version;
 #[derive(Args, Debug, PartialEq)]
 pub struct InstallOptions {
     /// skip sha256 verification.
@@ -11,12 +22,246 @@ pub struct InstallOptions {
     /// enable check archive size.
     #[arg(long)]
     pub enable_check_archive_size: bool,
-    /// registry index that is used to update Go version index.
-    #[arg(long, default_value_t = RegistryIndexType::Official(consts::GO_REGISTRY_INDEX.to_owned()), env = consts::GOUP_GO_REGISTRY_INDEX, value_parser = clap::value_parser!(RegistryIndexType))]
-    pub registry_index: RegistryIndexType,
+    #[command(flatten)]
+    pub index_options: IndexOptions,
     /// registry that is used to download Go archive file.
     #[arg(long, default_value_t = consts::GO_REGISTRY.to_owned(), env = consts::GOUP_GO_REGISTRY)]
     pub registry: String,
+    /// prefer the highest installed version satisfying the version request,
+    /// only fall back to the registry when none does.
+    #[arg(long, env = consts::GOUP_PREFER_INSTALLED, value_parser = clap::builder::FalseyValueParser::new())]
+    pub prefer_installed: bool,
+}
+
+impl InstallOptions {
+    /// the highest installed version satisfying the version request, if prefer installed.
+    pub fn preferred_installed(&self, version_req: &str) -> Result<Option<String>, anyhow::Error> {
+        if !self.prefer_installed {
+            return Ok(None);
+        }
+        let Ok(ver_req) = VersionReq::parse(version_req) else {
+            return Ok(None);
+        };
+        let versions = Version::list_go_version()?;
+        Ok(Version::match_installed(&versions, &ver_req).map(|v| v.version.clone()))
+    }
+
+    /// resolve the toolchain against the registry index and install it,
+    /// returns the normalized version, such as `go1.21.4` or `gotip`.
+    pub fn install_toolchain(
+        &self,
+        toolchain: &Toolchain,
+        cl: Option<&str>,
+        use_raw_version: bool,
+        caret: bool,
+    ) -> Result<String, anyhow::Error> {
+        let registry_index = self.index_options.as_registry_index();
+        let registry = Registry::new(
+            &self.registry,
+            self.skip_verify,
+            self.enable_check_archive_size,
+        )?;
+        let version = match toolchain {
+            Toolchain::Stable => {
+                let version = registry_index.get_upstream_latest_go_version()?;
+                let version = toolchain::normalize(&version);
+                let release = registry_index
+                    .local_index()
+                    .and_then(|v| v.find(&version).cloned());
+                registry.install_go(&version, release.as_ref())?;
+                version
+            }
+            Toolchain::Oldstable => {
+                let index: GoIndex = registry_index.list_upstream_releases()?.into();
+                let release = index
+                    .find(&index.secondary)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("failed get oldstable version"))?;
+                let version = toolchain::normalize(&release.version);
+                registry.install_go(&version, Some(&release))?;
+                version
+            }
+            Toolchain::Unstable => {
+                // 只选择比最新稳定版本更新的预发布版本
+                let stable =
+                    toolchain::semantic(&registry_index.get_upstream_latest_go_version()?)?;
+                let release = registry_index
+                    .list_upstream_releases_filter(Some(&ToolchainFilter::Unstable))?
+                    .into_iter()
+                    .rev()
+                    .find(|v| toolchain::semantic(&v.version).is_ok_and(|v| v > stable))
+                    .ok_or_else(|| anyhow!("no unstable version newer than the stable {stable}"))?;
+                let version = toolchain::normalize(&release.version);
+                registry.install_go(&version, Some(&release))?;
+                version
+            }
+            Toolchain::Beta => {
+                let release = registry_index
+                    .list_upstream_releases_filter(Some(&ToolchainFilter::Beta))?
+                    .pop()
+                    .ok_or_else(|| anyhow!("failed get latest beta version"))?;
+                let version = toolchain::normalize(&release.version);
+                registry.install_go(&version, Some(&release))?;
+                version
+            }
+            Toolchain::Version(version_req) => {
+                let release = if use_raw_version {
+                    registry_index
+                        .local_index()
+                        .and_then(|v| v.find(version_req).cloned())
+                        .unwrap_or_else(|| Release::new(version_req.trim_start_matches("go")))
+                } else if let Some(version) =
+                    self.preferred_installed(&toolchain::version_req(version_req, caret))?
+                {
+                    log::info!("Use installed Go {version} satisfying '{version_req}'");
+                    Release::new(version)
+                } else {
+                    let version_req = toolchain::version_req(version_req, caret);
+                    registry_index.match_release_req(&version_req).inspect_err(|_| {
+                        log::warn!("'semver' match failure, If you want to use version like '1.19beta1' or '1.25rc2', try add option '--use-raw-version'");
+                    })?
+                };
+                let version = toolchain::normalize(&release.version);
+                registry.install_go(&version, Some(&release))?;
+                version
+            }
+            Toolchain::Nightly => {
+                log::info!("Installing gotip ...");
+                NightlyRegistry::new(cl).install_go()?;
+                "gotip".to_owned()
+            }
+        };
+        Ok(version)
+    }
+
+    /// resolve the version request of `default` and `shell`, an installed version first (the highest match),
+    /// then the registry index the same as `install`, returns the normalized version.
+    pub fn resolve_version(
+        &self,
+        version: &str,
+        local_versions: &[Version],
+    ) -> Result<String, anyhow::Error> {
+        // 已安装的版本优先, 完全相同的版本, 否则为满足版本请求的最高版本
+        let exact = version.trim_start_matches("go");
+        if let Some(v) = local_versions.iter().find(|v| v.version == exact) {
+            return Ok(toolchain::normalize(&v.version));
+        }
+        let toolchain: Toolchain = version.parse()?;
+        let mut use_raw_version = false;
+        if let Toolchain::Version(ref v) = toolchain {
+            let version_req = toolchain::version_req(v, false);
+            match VersionReq::parse(&version_req) {
+                Ok(ver_req) => {
+                    if let Some(installed) = Version::match_installed(local_versions, &ver_req) {
+                        log::info!(
+                            "Use installed Go {} satisfying '{version_req}'",
+                            installed.version
+                        );
+                        return Ok(toolchain::normalize(&installed.version));
+                    }
+                }
+                // 非语义化的版本号, 如 1.19beta1, 按原始版本号安装
+                Err(_) => use_raw_version = true,
+            }
+        }
+        self.install_toolchain(&toolchain, None, use_raw_version, false)
+    }
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct IndexOptions {
+    /// registry index that is used to update Go version index,
+    /// several indexes separated by comma or given repeatedly are queried concurrently.
+    #[arg(long, default_value = consts::GO_REGISTRY_INDEX, env = consts::GOUP_GO_REGISTRY_INDEX, value_parser = clap::value_parser!(RegistryIndexes))]
+    pub registry_index: Vec<RegistryIndexes>,
+    /// merge and dedupe the versions of all registry indexes, instead of using the first successful one.
+    #[arg(long)]
+    pub merge_indexes: bool,
+    /// use the cached Go version index without any network call within the duration, such as `10m` or `1h`,
+    /// default `1h`. `goup search` revalidates the cached index with a conditional request unless it is set.
+    #[arg(long, env = consts::GOUP_INDEX_TTL, value_parser = clap::value_parser!(Age))]
+    pub index_ttl: Option<Age>,
+    /// force a fresh fetch of the Go version index, ignore the cached index.
+    #[arg(long)]
+    pub refresh: bool,
+}
+
+impl IndexOptions {
+    pub fn as_registry_index(&self) -> Box<dyn RegistryIndex> {
+        self.cached_index(
+            self.index_ttl
+                .map_or(consts::GO_INDEX_TTL_DURATION, |v| v.0),
+        )
+    }
+
+    /// the same as `as_registry_index`, but revalidate the cached index unless the ttl is set,
+    /// for listing the latest versions.
+    pub fn as_revalidated_registry_index(&self) -> Box<dyn RegistryIndex> {
+        self.cached_index(self.index_ttl.map_or(SignedDuration::ZERO, |v| v.0))
+    }
+
+    fn cached_index(&self, ttl: SignedDuration) -> Box<dyn RegistryIndex> {
+        let inner = match self.indexes().as_slice() {
+            [index] => index.as_registry_index(),
+            indexes => Box::new(MultiIndex::new(indexes.to_vec(), self.merge_indexes)),
+        };
+        Box::new(CachedIndex::new(inner, self.source(), ttl, self.refresh))
+    }
+
+    /// the local index of the registry indexes, if cached.
+    pub fn local_index(&self) -> Option<GoIndex> {
+        GoIndex::read(&self.source())
+    }
+
+    /// the registry indexes of all `--registry-index`.
+    fn indexes(&self) -> Vec<RegistryIndexType> {
+        self.registry_index
+            .iter()
+            .flat_map(|v| v.0.iter().cloned())
+            .collect()
+    }
+
+    /// the indexes the cached index comes from.
+    fn source(&self) -> String {
+        let indexes = self.indexes();
+        let source = indexes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        if self.merge_indexes && indexes.len() > 1 {
+            format!("merge:{source}")
+        } else {
+            source
+        }
+    }
+}
+
+/// warn if the selected version is end-of-life or superseded, by the local index if any,
+/// otherwise the cached or online index, a failed lookup is ignored.
+pub fn warn_lifecycle(index_options: &IndexOptions, version: &str) {
+    let index = index_options.local_index().or_else(|| {
+        index_options
+            .as_registry_index()
+            .list_upstream_releases()
+            .inspect_err(|e| log::debug!("Lookup the lifecycle of Go {version} failure: {e}"))
+            .ok()
+            .map(GoIndex::from)
+    });
+    let Some(index) = index else {
+        return;
+    };
+    match index.lifecycle(version) {
+        Some(lifecycle @ Lifecycle::EndOfLife(_)) => log::warn!(
+            "Go {version} is {lifecycle}, the supported releases are {} and {}",
+            index.latest,
+            index.secondary
+        ),
+        Some(lifecycle @ Lifecycle::Superseded(_)) => log::warn!(
+            "Go {version} is {lifecycle}, the latest patch of its minor line, update it with `goup update`"
+        ),
+        _ => {}
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -43,3 +288,101 @@ impl FromStr for KeyValuePair {
         })
     }
 }
+
+/// a byte size, such as `500M`, `2G` or `1.5GiB`, units are powers of 1024.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ByteSize(pub u64);
+
+impl FromStr for ByteSize {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let idx = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (num, unit) = s.split_at(idx);
+        let num = num
+            .parse::<f64>()
+            .map_err(|_| anyhow!("invalid size `{s}`, expected such as `500M` or `2G`"))?;
+        let shift = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 0,
+            "K" | "KB" | "KIB" => 10,
+            "M" | "MB" | "MIB" => 20,
+            "G" | "GB" | "GIB" => 30,
+            "T" | "TB" | "TIB" => 40,
+            _ => {
+                return Err(anyhow!(
+                    "invalid size unit `{unit}`, expected B, K, M, G or T"
+                ));
+            }
+        };
+        Ok(ByteSize((num * (1u64 << shift) as f64) as u64))
+    }
+}
+
+/// several registry indexes separated by comma, such as `official,https://golang.google.cn`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryIndexes(pub Vec<RegistryIndexType>);
+
+impl FromStr for RegistryIndexes {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        split_registry_indexes(s)
+            .into_iter()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// a duration, such as `30d`, `12h` or `2w`, a day is 24 hours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Age(pub SignedDuration);
+
+impl FromStr for Age {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let span: Span = s
+            .parse()
+            .map_err(|e| anyhow!("invalid duration `{s}`, expected such as `30d`: {e}"))?;
+        Ok(Age(span.to_duration(SpanRelativeTo::days_are_24_hours())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::SignedDuration;
+
+    use super::{Age, ByteSize};
+
+    #[test]
+    fn test_byte_size() {
+        assert_eq!("1024".parse::<ByteSize>().unwrap(), ByteSize(1024));
+        assert_eq!("500M".parse::<ByteSize>().unwrap(), ByteSize(500 << 20));
+        assert_eq!("2G".parse::<ByteSize>().unwrap(), ByteSize(2 << 30));
+        assert_eq!("2gb".parse::<ByteSize>().unwrap(), ByteSize(2 << 30));
+        assert_eq!("1.5GiB".parse::<ByteSize>().unwrap(), ByteSize(3 << 29));
+        assert!("2X".parse::<ByteSize>().is_err());
+        assert!("G".parse::<ByteSize>().is_err());
+    }
+
+    #[test]
+    fn test_age() {
+        assert_eq!(
+            "30d".parse::<Age>().unwrap(),
+            Age(SignedDuration::from_hours(30 * 24))
+        );
+        assert_eq!(
+            "2w".parse::<Age>().unwrap(),
+            Age(SignedDuration::from_hours(14 * 24))
+        );
+        assert_eq!(
+            "12h".parse::<Age>().unwrap(),
+            Age(SignedDuration::from_hours(12))
+        );
+        assert!("30x".parse::<Age>().is_err());
+    }
+}
@@ -1,4 +1,11 @@
 use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::anyhow;
+use jiff::SignedDuration;
+
+use crate::registries::registry_index::RegistryIndexType;
 
 // env key
 pub const GOUP_HOME: &str = "GOUP_HOME";
@@ -6,11 +13,22 @@ pub const GOUP_GO_VERSION: &str = "GOUP_GO_VERSION";
 pub const GOUP_GO_REGISTRY_INDEX: &str = "GOUP_GO_REGISTRY_INDEX";
 pub const GOUP_GO_REGISTRY: &str = "GOUP_GO_REGISTRY";
 pub const GOUP_GO_SOURCE_GIT_URL: &str = "GOUP_GO_SOURCE_GIT_URL";
+pub const GOUP_INDEX_TTL: &str = "GOUP_INDEX_TTL";
+pub const GOUP_OFFLINE: &str = "GOUP_OFFLINE";
+pub const GOUP_GO_VULNDB: &str = "GOUP_GO_VULNDB";
+pub const GOUP_GO_RELEASE_NOTES: &str = "GOUP_GO_RELEASE_NOTES";
+pub const GOUP_PREFER_INSTALLED: &str = "GOUP_PREFER_INSTALLED";
 // env value
 pub const GO_REGISTRY_INDEX: &str = "https://go.dev";
 pub const GO_REGISTRY: &str = "https://dl.google.com/go";
 pub const GO_SOURCE_GIT_URL: &str = "https://github.com/golang/go";
 pub const GO_SOURCE_UPSTREAM_GIT_URL: &str = "https://go.googlesource.com/go";
+pub const GO_PROXY: &str = "https://proxy.golang.org";
+pub const GO_SUMDB: &str = "sum.golang.org";
+pub const GO_INDEX_TTL: &str = "1h";
+/// the same as `GO_INDEX_TTL`.
+pub const GO_INDEX_TTL_DURATION: SignedDuration = SignedDuration::from_hours(1);
+pub const GO_VULNDB: &str = "https://vuln.go.dev";
 
 pub fn go_version() -> Option<String> {
     env::var(GOUP_GO_VERSION).ok().filter(|s| !s.is_empty())
@@ -24,6 +42,31 @@ pub fn go_registry() -> String {
     get_var_or_else(GOUP_GO_REGISTRY, || GO_REGISTRY.to_owned())
 }
 
+pub fn index_ttl() -> String {
+    get_var_or_else(GOUP_INDEX_TTL, || GO_INDEX_TTL.to_owned())
+}
+
+pub fn go_vulndb() -> String {
+    get_var_or_else(GOUP_GO_VULNDB, || GO_VULNDB.to_owned())
+}
+
+pub fn prefer_installed() -> String {
+    get_var_or_else(GOUP_PREFER_INSTALLED, || "false".to_owned())
+}
+
+/// base url of the release history page, default the host of the official registry index.
+pub fn go_release_notes() -> String {
+    get_var_or_else(GOUP_GO_RELEASE_NOTES, || {
+        go_registry_index()
+            .split(',')
+            .find_map(|v| match v.parse() {
+                Ok(RegistryIndexType::Official(host)) => Some(host),
+                _ => None,
+            })
+            .unwrap_or_else(|| GO_REGISTRY_INDEX.to_owned())
+    })
+}
+
 pub fn go_source_git_url() -> String {
     get_var_or_else(GOUP_GO_SOURCE_GIT_URL, || GO_SOURCE_GIT_URL.to_owned())
 }
@@ -34,6 +77,28 @@ pub fn go_source_upstream_git_url() -> String {
     })
 }
 
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// offline mode of `--offline` or `GOUP_OFFLINE`, resolve versions only from the local index,
+/// the installed versions and the download cache.
+pub fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// fails in offline mode, `what` needs the network.
+pub fn ensure_online(what: impl Display) -> Result<(), anyhow::Error> {
+    if offline() {
+        return Err(anyhow!(
+            "{what} needs the network, but offline mode is enabled(--offline or {GOUP_OFFLINE})"
+        ));
+    }
+    Ok(())
+}
+
 #[inline]
 fn get_var_or_else(key: &str, op: impl FnOnce() -> String) -> String {
     env::var(key)
@@ -77,6 +77,24 @@ impl Dir {
         d.push(p);
         d
     }
+    /// Extends `self` with `cache/objects`
+    pub fn cache_objects(&self) -> Self {
+        let mut d = self.join_path("cache");
+        d.push("objects");
+        d
+    }
+    /// Extends `self` with `cache/objects/{sha256}`
+    pub fn cache_object(&self, sha256: &str) -> Self {
+        let mut d = self.cache_objects();
+        d.push(sha256);
+        d
+    }
+    /// Extends `self` with `cache/tmp`
+    pub fn cache_tmp(&self) -> Self {
+        let mut d = self.join_path("cache");
+        d.push("tmp");
+        d
+    }
     /// Extends `self` with `{version}/.unpacked-success`
     fn version_dot_unpacked_success<P: AsRef<Path>>(&self, ver: P) -> Self {
         let mut d = self.join_path(ver);
@@ -103,10 +121,18 @@ impl Dir {
         File::create(&dot_unpacked_success_file)?;
         Ok(())
     }
-    /// Extends `self` with `index-go.json`
+    /// Extends `self` with `index-go.json`, the global index of old versions.
     pub fn index_go(&self) -> Self {
         self.join_path("index-go.json")
     }
+    /// Extends `self` with `index`
+    pub fn index(&self) -> Self {
+        self.join_path("index")
+    }
+    /// Extends `self` with `index/{name}`
+    pub fn index_file<P: AsRef<Path>>(&self, name: P) -> Self {
+        self.index().join_path(name)
+    }
 }
 
 impl AsRef<Path> for Dir {
@@ -178,6 +204,18 @@ mod tests {
             Dir::new(home_dir).cache_file("file").as_ref(),
             Path::new("/home/dev/.goup/cache/file")
         );
+        assert_eq!(
+            Dir::new(home_dir).cache_objects().as_ref(),
+            Path::new("/home/dev/.goup/cache/objects")
+        );
+        assert_eq!(
+            Dir::new(home_dir).cache_object("e2bc0b").as_ref(),
+            Path::new("/home/dev/.goup/cache/objects/e2bc0b")
+        );
+        assert_eq!(
+            Dir::new(home_dir).cache_tmp().as_ref(),
+            Path::new("/home/dev/.goup/cache/tmp")
+        );
         assert_eq!(
             Dir::new(home_dir).version("go1.21.2").as_ref(),
             Path::new("/home/dev/.goup/go1.21.2")
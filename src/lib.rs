@@ -1,10 +1,14 @@
-mod archived;
+pub mod archived;
+mod cache;
 mod command;
 mod consts;
 mod dir;
+mod progress;
 pub mod registries;
+mod release_notes;
 mod shell;
 mod toolchain;
 mod version;
+mod vulndb;
 
 pub use command::Cli;
@@ -0,0 +1,170 @@
+use std::io::{self, IsTerminal, Read};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressStyle};
+
+/// interval of the plain log line when stderr is not a terminal.
+const PLAIN_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Bytes,
+    Entries,
+}
+
+/// Progress of a long-running step, such as download, verify and unpack.
+///
+/// Draw a progress bar in the `MultiProgress` when stderr is a terminal,
+/// otherwise degrade to periodic plain log lines.
+pub(crate) struct Progress {
+    mp: MultiProgress,
+    pb: ProgressBar,
+    unit: Unit,
+    message: String,
+    // last time of the plain log line, `None` if drawing a progress bar.
+    plain: Option<Mutex<Instant>>,
+}
+
+impl Progress {
+    /// a byte-based progress.
+    pub(crate) fn bytes(mp: &MultiProgress, total: u64, message: impl Into<String>) -> Self {
+        Self::new(mp, total, Unit::Bytes, message.into())
+    }
+    /// an entry-based progress.
+    pub(crate) fn entries(mp: &MultiProgress, total: u64, message: impl Into<String>) -> Self {
+        Self::new(mp, total, Unit::Entries, message.into())
+    }
+
+    fn new(mp: &MultiProgress, total: u64, unit: Unit, message: String) -> Self {
+        let is_terminal = io::stderr().is_terminal();
+        let pb = if is_terminal {
+            let template = match unit {
+                Unit::Bytes => {
+                    "  [{elapsed_precise}] [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({eta}) {msg}"
+                }
+                Unit::Entries => "  [{elapsed_precise}] [{bar:30.cyan/blue}] {pos}/{len} {msg}",
+            };
+            let pb = mp.add(ProgressBar::new(total));
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template(template)
+                    .unwrap_or_else(|_| ProgressStyle::default_bar())
+                    .progress_chars("=> "),
+            );
+            pb.set_message(message.clone());
+            pb.enable_steady_tick(Duration::from_millis(100));
+            pb
+        } else {
+            ProgressBar::hidden()
+        };
+        pb.set_length(total);
+        Self {
+            mp: mp.clone(),
+            pb,
+            unit,
+            message,
+            plain: (!is_terminal).then(|| Mutex::new(Instant::now())),
+        }
+    }
+
+    /// advances the position.
+    pub(crate) fn inc(&self, delta: u64) {
+        self.pb.inc(delta);
+        if let Some(ref last) = self.plain
+            && let Ok(mut last) = last.lock()
+            && last.elapsed() >= PLAIN_LOG_INTERVAL
+        {
+            *last = Instant::now();
+            self.log_plain();
+        }
+    }
+
+    /// wraps a reader, advances the position with the bytes read.
+    pub(crate) fn wrap_read<R: Read>(&self, read: R) -> ProgressRead<'_, R> {
+        ProgressRead {
+            progress: self,
+            inner: read,
+        }
+    }
+
+    /// finish and clear the progress.
+    pub(crate) fn finish(&self) {
+        if self.plain.is_some() {
+            self.log_plain();
+        }
+        self.pb.finish_and_clear();
+        self.mp.remove(&self.pb);
+    }
+
+    fn log_plain(&self) {
+        let pos = self.pb.position();
+        let len = self.pb.length().unwrap_or_default();
+        let percent = (pos * 100).checked_div(len).unwrap_or(100);
+        match self.unit {
+            Unit::Bytes => log::info!(
+                "{}: {}/{} ({percent}%)",
+                self.message,
+                HumanBytes(pos),
+                HumanBytes(len)
+            ),
+            Unit::Entries => log::info!("{}: {pos}/{len} ({percent}%)", self.message),
+        }
+    }
+}
+
+impl Drop for Progress {
+    // clear the progress bar on early return, such as an error.
+    fn drop(&mut self) {
+        self.pb.finish_and_clear();
+        self.mp.remove(&self.pb);
+    }
+}
+
+/// A reader advances the progress with the bytes read.
+pub(crate) struct ProgressRead<'a, R> {
+    progress: &'a Progress,
+    inner: R,
+}
+
+impl<R: Read> Read for ProgressRead<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.inc(n as u64);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use indicatif::MultiProgress;
+
+    use super::Progress;
+
+    #[test]
+    fn test_wrap_read() -> Result<(), anyhow::Error> {
+        let mp = MultiProgress::new();
+        let progress = Progress::bytes(&mp, 11, "reading");
+        let mut buf = String::new();
+        progress
+            .wrap_read("hello world".as_bytes())
+            .read_to_string(&mut buf)?;
+        assert_eq!(buf, "hello world");
+        assert_eq!(progress.pb.position(), 11);
+        progress.finish();
+
+        let progress = Progress::entries(&mp, 3, "unpacking");
+        (0..3).for_each(|_| progress.inc(1));
+        assert_eq!(progress.pb.position(), 3);
+        progress.finish();
+
+        // cleared without finish, such as an error.
+        let progress = Progress::bytes(&mp, 11, "downloading");
+        let pb = progress.pb.clone();
+        drop(progress);
+        assert!(pb.is_finished());
+        Ok(())
+    }
+}
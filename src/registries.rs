@@ -1,3 +1,6 @@
 pub mod go_index;
+pub mod goproxy;
 pub mod registry;
 pub mod registry_index;
+pub mod release;
+pub mod s3;
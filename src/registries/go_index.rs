@@ -1,147 +1,497 @@
-use std::fs::File;
+use std::fmt::{self, Display};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::process;
 
 use anyhow::anyhow;
-use semver::{Version, VersionReq};
+use jiff::{SignedDuration, Timestamp};
+use semver::VersionReq;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::{Map, Value};
 use sha2::{Digest, Sha256};
 
+use crate::consts;
 use crate::dir::Dir;
+use crate::registries::registry_index::RegistryIndexType;
+use crate::registries::release::Release;
 use crate::toolchain;
 
 #[derive(Debug)]
 pub enum Resolution {
-    Resolved(String), // 已确定版本
-    Unresolved,       // 未确定, 需要进一步确定
+    Resolved(Release), // 已确定版本
+    Unresolved,        // 未确定, 需要进一步确定
+}
+
+/// lifecycle of a go version, Go supports the two latest minor lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lifecycle {
+    /// the latest patch of a supported minor line.
+    Supported,
+    /// superseded by the newer patch of the same supported minor line.
+    Superseded(String),
+    /// the minor line is no longer supported, upgrade to the latest stable version.
+    EndOfLife(String),
+}
+
+impl Display for Lifecycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Supported => write!(f, "supported"),
+            Self::Superseded(target) => write!(f, "superseded by {target}"),
+            Self::EndOfLife(target) => write!(f, "end-of-life, upgrade to {target}"),
+        }
+    }
+}
+
+/// validators of the conditional request, from the `ETag` and `Last-Modified` response headers.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct GoIndex {
-    pub versions: Vec<String>, // 已发布go版本列表
-    pub latest: String,        // 最新稳定版本
-    pub secondary: String,     // 次新稳定版本
-    pub sha256: String,        // 版本列表的sha256
+    pub releases: Vec<Release>, // 已发布go版本列表
+    pub latest: String,         // 最新稳定版本
+    pub secondary: String,      // 次新稳定版本
+    pub sha256: String,         // 版本列表的sha256
+    #[serde(default)]
+    pub source: String, // 版本列表的来源索引
+    #[serde(default)]
+    pub validators: Validators, // 条件请求的校验值
+    #[serde(default)]
+    pub fetched_at: Option<Timestamp>, // 上次从上游获取的时间
 }
 
 impl GoIndex {
-    pub fn read() -> Option<GoIndex> {
+    /// read the index of the registry index source, `None` if not cached.
+    pub fn read(source: &str) -> Option<GoIndex> {
         let goup_home = Dir::goup_home().ok()?;
-        let index_go = goup_home.index_go();
-        if index_go.exists() {
-            let file = File::open(index_go).ok()?;
-            Some(serde_json::from_reader(file).ok()?)
-        } else {
-            None
+        migrate_legacy(&goup_home);
+        let path = goup_home.index_file(source_key(source));
+        if !path.exists() {
+            return None;
         }
+        load(&path)
+            .inspect_err(|e| log::warn!("Ignore index {}: {e}", path.display()))
+            .ok()
+            .filter(|v| v.source == source)
     }
-    pub fn write_if_change(index: &GoIndex) -> Result<(), anyhow::Error> {
-        let index_go = Dir::goup_home()?.index_go();
-        if index_go.exists()
-            && let Ok(file) = File::open(&index_go)
-            && let Ok(old) = serde_json::from_reader::<_, GoIndex>(file)
-            && old.sha256 == index.sha256
-        {
-            return Ok(());
+    /// read the indexes of all registry index sources.
+    pub fn read_all() -> Vec<GoIndex> {
+        let Ok(goup_home) = Dir::goup_home() else {
+            return Vec::new();
+        };
+        migrate_legacy(&goup_home);
+        let Ok(dir) = fs::read_dir(goup_home.index()) else {
+            return Vec::new();
+        };
+        dir.filter_map(|v| v.ok().map(|v| v.path()))
+            .filter(|v| v.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                load(&path)
+                    .inspect_err(|e| log::warn!("Ignore index {}: {e}", path.display()))
+                    .ok()
+            })
+            .collect()
+    }
+    /// write the index of its source atomically, with the current schema version.
+    pub fn write(&self) -> Result<(), anyhow::Error> {
+        let goup_home = Dir::goup_home()?;
+        // 先迁移旧的全局索引, 避免覆盖新写入的索引
+        migrate_legacy(&goup_home);
+        fs::create_dir_all(goup_home.index())?;
+        write_atomic(&goup_home.index_file(source_key(&self.source)), self)
+    }
+    // 是否在有效期内
+    pub fn is_fresh(&self, ttl: SignedDuration) -> bool {
+        self.fetched_at.is_some_and(|v| {
+            let age = Timestamp::now().duration_since(v);
+            !age.is_negative() && age < ttl
+        })
+    }
+    // 版本的生命周期, 非语义化版本(如gotip)或索引为空时为None
+    pub fn lifecycle(&self, version: &str) -> Option<Lifecycle> {
+        let version = toolchain::semantic(version.trim_start_matches("go")).ok()?;
+        let secondary = toolchain::semantic(&self.secondary).ok()?;
+        if (version.major, version.minor) < (secondary.major, secondary.minor) {
+            return Some(Lifecycle::EndOfLife(self.latest.clone()));
         }
-        let file = File::create(&index_go)?;
-        serde_json::to_writer(file, index)?;
-        Ok(())
+        // 同一次版本的最新补丁版本
+        let patch = self
+            .releases
+            .iter()
+            .filter(|v| v.stable)
+            .filter_map(|v| toolchain::semantic(&v.version).ok().map(|s| (s, v)))
+            .filter(|(s, _)| (s.major, s.minor) == (version.major, version.minor))
+            .max_by(|a, b| a.0.cmp(&b.0));
+        Some(match patch {
+            Some((semver, release)) if semver > version => {
+                Lifecycle::Superseded(release.version.clone())
+            }
+            _ => Lifecycle::Supported,
+        })
+    }
+    // 已发布go版本列表
+    pub fn versions(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.releases.iter().map(|v| v.version.as_str())
+    }
+    // 查找版本
+    pub fn find(&self, version: &str) -> Option<&Release> {
+        let version = version.trim_start_matches("go");
+        self.releases.iter().find(|v| v.version == version)
     }
     // 匹配本地版本
-    pub fn match_version(&self, ver_req: &VersionReq) -> Option<String> {
-        self.versions
+    pub fn match_release(&self, ver_req: &VersionReq) -> Option<&Release> {
+        self.releases
             .iter()
             .rev()
-            .find_map(|v| {
-                toolchain::semantic(v)
-                    .ok()
-                    .filter(|semver| ver_req.matches(semver))
-                    .map(|_| v)
-            })
-            .map(ToOwned::to_owned)
+            .find(|v| toolchain::semantic(&v.version).is_ok_and(|semver| ver_req.matches(&semver)))
+    }
+    // 匹配本地版本号
+    pub fn match_version(&self, ver_req: &VersionReq) -> Option<String> {
+        self.match_release(ver_req).map(|v| v.version.clone())
     }
-    // 尝试匹配归档版本
+    // 尝试匹配归档版本, 本地最佳匹配版本不会被将来发布的版本取代时确定
     pub fn try_match_archived_version(
         &self,
         ver_req: &VersionReq,
     ) -> Result<Resolution, anyhow::Error> {
-        if self.versions.is_empty() || self.latest.is_empty() || self.secondary.is_empty() {
+        if self.releases.is_empty() || self.latest.is_empty() || self.secondary.is_empty() {
             return Ok(Resolution::Unresolved);
         }
-        if ver_req.comparators.len() != 1 {
-            // 先匹配本地版本
-            let ver = self.match_version(ver_req);
-            let search_type = if let Some(ver) = ver
-                && (ver != self.latest || ver != self.secondary)
-            {
-                Resolution::Resolved(ver)
+        let Some(release) = self.match_release(ver_req) else {
+            return Ok(Resolution::Unresolved);
+        };
+        let latest = toolchain::semantic(&self.latest)?;
+        let secondary = toolchain::semantic(&self.secondary)?;
+        let best = toolchain::semantic(&release.version)?;
+        Ok(
+            if toolchain::is_match_archived(&latest, &secondary, ver_req, &best) {
+                Resolution::Resolved(release.clone())
             } else {
                 Resolution::Unresolved
-            };
-            return Ok(search_type);
-        }
+            },
+        )
+    }
+}
 
-        let latest = toolchain::semantic(&self.latest)?;
-        let secondary = toolchain::semantic(&self.secondary)?;
-        let is_match_archived = toolchain::is_match_archived(&latest, &secondary, ver_req);
-        if is_match_archived {
-            self.match_version(ver_req)
-                .map(Resolution::Resolved)
-                .ok_or_else(|| anyhow!("no matching version found!"))
+/// the index file with its schema version.
+#[derive(Serialize)]
+struct Versioned<'a> {
+    schema: u64,
+    #[serde(flatten)]
+    index: &'a GoIndex,
+}
+
+/// file name of the registry index source, `index/{sha256 prefix}.json`.
+fn source_key(source: &str) -> String {
+    format!("{}.json", &hex::encode(Sha256::digest(source))[..16])
+}
+
+fn load(path: &Path) -> Result<GoIndex, anyhow::Error> {
+    let value = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+    Ok(serde_json::from_value(migrate(value)?)?)
+}
+
+fn write_atomic(path: &Path, index: &GoIndex) -> Result<(), anyhow::Error> {
+    let tmp_file = path.with_extension(format!("json.{}.tmp", process::id()));
+    let versioned = Versioned {
+        schema: SCHEMA_VERSION,
+        index,
+    };
+    let result = File::create(&tmp_file)
+        .map_err(anyhow::Error::from)
+        .and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, &versioned)?;
+            writer.into_inner()?.sync_all()?;
+            Ok(())
+        })
+        .and_then(|_| Ok(fs::rename(&tmp_file, path)?));
+    if result.is_err() {
+        fs::remove_file(&tmp_file).ok();
+    }
+    result
+}
+
+/// move the global `index-go.json` of old versions to the index of its source, the index of the
+/// default registry index(`GOUP_GO_REGISTRY_INDEX`) if not recorded, so that the online reads use it.
+fn migrate_legacy(goup_home: &Dir) {
+    let legacy = goup_home.index_go();
+    if !legacy.exists() {
+        return;
+    }
+    let result = load(&legacy).and_then(|mut index| {
+        if index.source.is_empty() {
+            index.source = default_source();
+        }
+        let path = goup_home.index_file(source_key(&index.source));
+        // 已有该来源的索引时, 旧索引不再需要
+        if path.exists() {
+            log::info!(
+                "Remove legacy index {}, the index of {} exists",
+                legacy.display(),
+                index.source
+            );
         } else {
-            Ok(Resolution::Unresolved)
+            fs::create_dir_all(goup_home.index())?;
+            write_atomic(&path, &index)?;
+            log::debug!(
+                "Migrated legacy index {} to the index of {}",
+                legacy.display(),
+                index.source
+            );
         }
+        Ok(fs::remove_file(&legacy)?)
+    });
+    if let Err(e) = result {
+        log::warn!("Migrate legacy index {} failed: {e}", legacy.display());
+    }
+}
+
+/// the source of the default registry indexes, the same as `--registry-index` without merging.
+fn default_source() -> String {
+    consts::go_registry_index()
+        .split(',')
+        .map(|v| {
+            v.parse::<RegistryIndexType>()
+                .map_or_else(|_| v.to_owned(), |v| v.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// current schema version of the index file.
+///
+/// 1. `versions`, the list of version strings.
+/// 2. `releases` with files, without schema version.
+/// 3. versioned, per registry index source, with the fetch metadata.
+pub const SCHEMA_VERSION: u64 = 3;
+
+/// migrates the index file object of a schema to the next.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), anyhow::Error>;
+
+/// forward migrations, `MIGRATIONS[n - 1]` migrates schema `n` to `n + 1`.
+const MIGRATIONS: [Migration; 2] = [migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// migrate the index file of any older schema to the current schema.
+fn migrate(value: Value) -> Result<Value, anyhow::Error> {
+    let Value::Object(mut object) = value else {
+        return Err(anyhow!("invalid index, expected a json object"));
+    };
+    let schema = match object.get("schema") {
+        Some(v) => v
+            .as_u64()
+            .filter(|v| *v > 0)
+            .ok_or_else(|| anyhow!("invalid index schema version {v}"))?,
+        None if object.contains_key("versions") => 1,
+        None => 2,
+    };
+    if schema > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "index schema version {schema} is newer than {SCHEMA_VERSION}, written by a newer goup"
+        ));
+    }
+    for migration in &MIGRATIONS[(schema - 1) as usize..] {
+        migration(&mut object)?;
     }
+    object.insert("schema".to_owned(), SCHEMA_VERSION.into());
+    Ok(Value::Object(object))
+}
+
+// `versions` -> `releases`
+fn migrate_v1_to_v2(object: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    let versions: Vec<String> = match object.remove("versions") {
+        Some(v) => serde_json::from_value(v)?,
+        None => Vec::new(),
+    };
+    let releases = versions.into_iter().map(Release::new).collect::<Vec<_>>();
+    object.insert("releases".to_owned(), serde_json::to_value(releases)?);
+    Ok(())
+}
+
+// the source of the global index is unknown, if not recorded, `migrate_legacy` takes the default registry index.
+fn migrate_v2_to_v3(object: &mut Map<String, Value>) -> Result<(), anyhow::Error> {
+    object
+        .entry("source")
+        .or_insert_with(|| Value::String(String::new()));
+    Ok(())
 }
 
 impl From<Vec<String>> for GoIndex {
     fn from(versions: Vec<String>) -> Self {
-        let mut version: Vec<(Version, String)> = versions
+        versions
             .into_iter()
-            .filter_map(|v| toolchain::semantic(&v).ok().map(|ver| (ver, v)))
-            .collect();
-        version.sort_by(|a, b| a.0.cmp(&b.0));
+            .map(Release::new)
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl From<Vec<Release>> for GoIndex {
+    fn from(mut releases: Vec<Release>) -> Self {
+        Release::sort(&mut releases);
 
         let mut latest: Option<(u64, u64, String)> = None;
         let mut secondary: Option<String> = None;
         // 反向迭代, 找到最新的稳定版本和次新的稳定版本
-        for (ver, raw) in version.iter().rev() {
+        for release in releases.iter().rev() {
+            let Ok(ver) = toolchain::semantic(&release.version) else {
+                continue;
+            };
             if !ver.pre.is_empty() {
                 continue; // 跳过 prerelease (rc/beta/alpha)
             }
             let Some(ref cur) = latest else {
-                latest = Some((ver.major, ver.minor, raw.clone()));
+                latest = Some((ver.major, ver.minor, release.version.clone()));
                 continue;
             };
             if ver.major <= cur.0 && ver.minor < cur.1 {
-                secondary = Some(raw.clone());
+                secondary = Some(release.version.clone());
                 break;
             }
         }
         let latest = latest.map(|v| v.2).unwrap_or_default();
         let secondary = secondary.unwrap_or_else(|| latest.clone());
 
+        // 版本及其文件的sha256
         let mut context = Sha256::new();
-        let versions = version
-            .into_iter()
-            .map(|v| {
-                context.update(&v.1);
-                v.1
-            })
-            .collect::<Vec<String>>();
+        for release in &releases {
+            context.update(&release.version);
+            for file in &release.files {
+                context.update(&file.filename);
+                context.update(&file.sha256);
+            }
+        }
         let sha256 = hex::encode(context.finalize());
         Self {
-            versions,
+            releases,
             latest,
             secondary,
             sha256,
+            ..Default::default()
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GoIndex;
+    use std::fs;
+
+    use serde_json::json;
+
+    use super::{GoIndex, Lifecycle, SCHEMA_VERSION, migrate};
+    use crate::consts;
+    use crate::dir::Dir;
+    use crate::registries::release::Release;
+
+    #[test]
+    fn test_lifecycle() {
+        let index: GoIndex = vec![
+            "1.21.0", "1.21.13", "1.22.0", "1.22.1", "1.22.2", "1.23rc1", "1.23.0", "1.23.1",
+            "1.24rc1",
+        ]
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>()
+        .into();
+        let lifecycle = |v| index.lifecycle(v);
+        assert_eq!(
+            lifecycle("1.21.13"),
+            Some(Lifecycle::EndOfLife("1.23.1".to_owned()))
+        );
+        assert_eq!(
+            lifecycle("go1.22.1"),
+            Some(Lifecycle::Superseded("1.22.2".to_owned()))
+        );
+        assert_eq!(lifecycle("1.22.2"), Some(Lifecycle::Supported));
+        assert_eq!(
+            lifecycle("1.23rc1"),
+            Some(Lifecycle::Superseded("1.23.1".to_owned()))
+        );
+        assert_eq!(lifecycle("1.23.1"), Some(Lifecycle::Supported));
+        assert_eq!(lifecycle("1.24rc1"), Some(Lifecycle::Supported));
+        assert_eq!(lifecycle("tip"), None);
+        assert_eq!(GoIndex::default().lifecycle("1.22.2"), None);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let v1 = json!({
+            "versions": ["1.21.0", "1.22rc1"],
+            "latest": "1.21.0",
+            "secondary": "1.21.0",
+            "sha256": "aa",
+        });
+        let index: GoIndex = serde_json::from_value(migrate(v1).unwrap()).unwrap();
+        assert_eq!(
+            index.versions().collect::<Vec<_>>(),
+            vec!["1.21.0", "1.22rc1"]
+        );
+        assert!(index.releases[0].stable && !index.releases[1].stable);
+        assert_eq!(index.source, "");
+
+        let v2 = json!({
+            "releases": [{"version": "1.21.0", "stable": true, "files": []}],
+            "latest": "1.21.0",
+            "secondary": "1.21.0",
+            "sha256": "aa",
+        });
+        let v3 = migrate(v2).unwrap();
+        assert_eq!(v3["schema"], SCHEMA_VERSION);
+        assert_eq!(v3["source"], "");
+
+        let newer = json!({"schema": SCHEMA_VERSION + 1, "releases": []});
+        assert!(
+            migrate(newer)
+                .unwrap_err()
+                .to_string()
+                .contains("newer goup")
+        );
+        assert!(migrate(json!([])).is_err());
+    }
+
+    #[test]
+    fn test_read_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let vars = [
+            (consts::GOUP_HOME, Some(dir.path().as_os_str())),
+            (consts::GOUP_GO_REGISTRY_INDEX, None),
+        ];
+        temp_env::with_vars(vars, || {
+            let goup_home = Dir::goup_home().unwrap();
+            // the global index of old versions
+            fs::write(
+                goup_home.index_go(),
+                r#"{"versions":["1.21.0"],"latest":"1.21.0","secondary":"1.21.0","sha256":""}"#,
+            )
+            .unwrap();
+            let mut index: GoIndex = vec![Release::new("1.22.0")].into();
+            index.source = "official+https://golang.google.cn".to_owned();
+            index.write().unwrap();
+
+            assert!(!goup_home.index_go().exists());
+            let read = GoIndex::read("official+https://golang.google.cn").unwrap();
+            assert_eq!(read.versions().collect::<Vec<_>>(), vec!["1.22.0"]);
+            // the global index is migrated to the index of the default registry index.
+            let read = GoIndex::read("official+https://go.dev").unwrap();
+            assert_eq!(read.versions().collect::<Vec<_>>(), vec!["1.21.0"]);
+            assert!(GoIndex::read("json+https://example.com/index.json").is_none());
+            assert_eq!(GoIndex::read_all().len(), 2);
+
+            let files = fs::read_dir(goup_home.index())
+                .unwrap()
+                .map(|v| v.unwrap().file_name().into_string().unwrap())
+                .collect::<Vec<_>>();
+            assert!(files.iter().all(|v| v.ends_with(".json")));
+        });
+    }
 
     #[test]
     fn test_impl_from_trait() {
@@ -157,7 +507,7 @@ mod tests {
 
             let v2 = v1.iter().map(|s| s.to_string()).collect::<Vec<String>>();
             let cgv: GoIndex = v2.into();
-            assert_eq!(cgv.versions, want);
+            assert_eq!(cgv.versions().collect::<Vec<_>>(), want);
             assert_eq!(cgv.latest, "1.25.3");
             assert_eq!(cgv.secondary, "1.24.2");
         }
@@ -190,7 +540,7 @@ mod tests {
             ];
             let v2 = v1.iter().map(|s| s.to_string()).collect::<Vec<String>>();
             let cgv: GoIndex = v2.into();
-            assert_eq!(cgv.versions, want);
+            assert_eq!(cgv.versions().collect::<Vec<_>>(), want);
             assert_eq!(cgv.latest, "1.24.2");
             assert_eq!(cgv.secondary, "1.23.1");
         }
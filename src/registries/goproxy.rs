@@ -0,0 +1,277 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::blocking::Client;
+use sha2::{Digest, Sha256};
+use zip::ZipArchive;
+
+use crate::consts;
+use crate::registries::release::GoFile;
+
+/// module of the Go toolchains, published since Go 1.21.
+pub const TOOLCHAIN_MODULE: &str = "golang.org/toolchain";
+/// version prefix of the toolchain module, `v0.0.1-go1.21.0.linux-amd64`.
+const TOOLCHAIN_MODULE_VERSION_PREFIX: &str = "v0.0.1-go";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// a GOPROXY serving the `golang.org/toolchain` module, such as Athens or Artifactory.
+#[derive(Debug, Clone)]
+pub struct GoProxy {
+    url: String,
+}
+
+impl GoProxy {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// module version of the toolchain, go1.21.0 -> v0.0.1-go1.21.0.linux-amd64
+    pub fn module_version(version: &str, os: &str, arch: &str) -> String {
+        format!(
+            "{TOOLCHAIN_MODULE_VERSION_PREFIX}{}.{os}-{arch}",
+            version.trim_start_matches("go")
+        )
+    }
+
+    /// url of the module zip.
+    pub fn archive_url(&self, module_version: &str) -> String {
+        format!("{}/{TOOLCHAIN_MODULE}/@v/{module_version}.zip", self.url)
+    }
+
+    /// split the url of the module zip into the proxy url and the module version,
+    /// `None` if it is not a module zip of the toolchain.
+    pub fn split_archive_url(url: &str) -> Option<(&str, &str)> {
+        let (proxy, file) = url.split_once(&format!("/{TOOLCHAIN_MODULE}/@v/"))?;
+        let module_version = file.strip_suffix(".zip")?;
+        module_version
+            .starts_with(TOOLCHAIN_MODULE_VERSION_PREFIX)
+            .then_some((proxy, module_version))
+    }
+
+    /// list toolchain files of `@v/list`, (version, file).
+    /// the file name is `go{version}.{os}-{arch}.zip`, the name of the archive in the cache.
+    pub fn list_files(&self) -> Result<Vec<(String, GoFile)>, anyhow::Error> {
+        let url = format!("{}/{TOOLCHAIN_MODULE}/@v/list", self.url);
+        let text = fetch_text(&url)?;
+        Ok(text
+            .lines()
+            .filter_map(Self::parse_module_version)
+            .collect())
+    }
+
+    /// v0.0.1-go1.21.0.linux-amd64 -> (1.21.0, go1.21.0.linux-amd64.zip)
+    fn parse_module_version(module_version: &str) -> Option<(String, GoFile)> {
+        let name = module_version
+            .trim()
+            .strip_prefix(TOOLCHAIN_MODULE_VERSION_PREFIX)?;
+        let (version, platform) = name.rsplit_once('.')?;
+        let (os, arch) = platform.split_once('-')?;
+        (!version.is_empty() && !os.is_empty() && !arch.is_empty()).then(|| {
+            (
+                version.to_owned(),
+                GoFile::new(&format!("go{name}.zip"), os, arch),
+            )
+        })
+    }
+
+    /// the `h1:` hash of the module zip, from the `.ziphash` of the local module cache if any,
+    /// otherwise the go.sum line of the checksum database lookup through the proxy, or directly.
+    ///
+    /// NOTE: the lookup line is trusted as is, neither the signed note nor the tree of the
+    /// checksum database is verified, so it is no stronger than the TLS connection to the proxy.
+    pub fn lookup_h1(&self, module_version: &str) -> Result<String, anyhow::Error> {
+        if let Some(dir) = go_mod_cache() {
+            let ziphash = dir
+                .join("cache/download")
+                .join(TOOLCHAIN_MODULE)
+                .join("@v")
+                .join(format!("{module_version}.ziphash"));
+            if let Ok(h1) = fs::read_to_string(&ziphash) {
+                log::debug!("Use h1 hash of {}", ziphash.display());
+                return Ok(h1.trim().to_owned());
+            }
+        }
+        consts::ensure_online(format!(
+            "looking up {TOOLCHAIN_MODULE}@{module_version} in checksum database"
+        ))?;
+        let path = format!("lookup/{TOOLCHAIN_MODULE}@{module_version}");
+        let text = fetch_text(&format!("{}/sumdb/{}/{path}", self.url, consts::GO_SUMDB))
+            .or_else(|_| fetch_text(&format!("https://{}/{path}", consts::GO_SUMDB)))?;
+        parse_go_sum(&text, module_version).ok_or_else(|| {
+            anyhow!("no h1 hash of {TOOLCHAIN_MODULE}@{module_version} in checksum database")
+        })
+    }
+}
+
+/// the `h1:` dirhash of the module zip, the same as `go mod download` computes.
+/// sha256 over the lines of `{sha256 of file}  {file name}\n`, sorted by file name.
+pub fn hash_zip<P: AsRef<Path>>(path: P) -> Result<String, anyhow::Error> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut files = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_owned();
+        if name.contains('\n') {
+            return Err(anyhow!("dirhash: file name with newline {name:?}"));
+        }
+        let mut hasher = Sha256::new();
+        let mut buffer = [0; 64 * 1024];
+        loop {
+            let bytes_read = file.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
+        }
+        files.push((name, hex::encode(hasher.finalize())));
+    }
+    // 与 dirhash.Hash1 一致, 按文件名排序, 而不是按整行排序
+    files.sort_unstable();
+    let lines: String = files
+        .iter()
+        .map(|(name, sha256)| format!("{sha256}  {name}\n"))
+        .collect();
+    let summary = Sha256::digest(lines.as_bytes());
+    Ok(format!("h1:{}", STANDARD.encode(summary)))
+}
+
+/// find the `h1:` hash of the module version in go.sum lines.
+/// golang.org/toolchain v0.0.1-go1.21.0.linux-amd64 h1:...
+fn parse_go_sum(text: &str, module_version: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        (fields.next()? == TOOLCHAIN_MODULE && fields.next()? == module_version)
+            .then(|| fields.next())
+            .flatten()
+            .filter(|v| v.starts_with("h1:"))
+            .map(ToOwned::to_owned)
+    })
+}
+
+/// GOMODCACHE, default `$GOPATH/pkg/mod`, `$HOME/go/pkg/mod`.
+fn go_mod_cache() -> Option<PathBuf> {
+    let var = |key| env::var_os(key).filter(|v| !v.is_empty());
+    if let Some(dir) = var("GOMODCACHE") {
+        return Some(PathBuf::from(dir));
+    }
+    let gopath = match var("GOPATH") {
+        Some(v) => env::split_paths(&v).next()?,
+        None => dirs::home_dir()?.join("go"),
+    };
+    Some(gopath.join("pkg/mod"))
+}
+
+fn fetch_text(url: &str) -> Result<String, anyhow::Error> {
+    consts::ensure_online(format!("fetching {url}"))?;
+    let resp = Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()?
+        .get(url)
+        .header("User-Agent", env!("CARGO_PKG_VERSION"))
+        .send()?;
+    if !resp.status().is_success() {
+        return Err(anyhow!("Fetching {url} failure, status {}", resp.status()));
+    }
+    let mut text = String::new();
+    resp.take(16 << 20).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    use super::{GoProxy, hash_zip, parse_go_sum};
+    use crate::registries::release::FileKind;
+
+    #[test]
+    fn test_module_version() {
+        assert_eq!(
+            GoProxy::module_version("go1.21.0", "linux", "amd64"),
+            "v0.0.1-go1.21.0.linux-amd64"
+        );
+        let proxy = GoProxy::new("https://goproxy.example.com/");
+        assert_eq!(
+            proxy.archive_url("v0.0.1-go1.21.0.linux-amd64"),
+            "https://goproxy.example.com/golang.org/toolchain/@v/v0.0.1-go1.21.0.linux-amd64.zip"
+        );
+        assert_eq!(
+            GoProxy::split_archive_url(&proxy.archive_url("v0.0.1-go1.21.0.linux-amd64")),
+            Some(("https://goproxy.example.com", "v0.0.1-go1.21.0.linux-amd64"))
+        );
+        assert!(GoProxy::split_archive_url("https://go.dev/dl/go1.21.0.linux-amd64.zip").is_none());
+        let (version, file) =
+            GoProxy::parse_module_version("v0.0.1-go1.22rc1.windows-arm64").unwrap();
+        assert_eq!(version, "1.22rc1");
+        assert_eq!(file.filename, "go1.22rc1.windows-arm64.zip");
+        assert_eq!((file.os.as_str(), file.arch.as_str()), ("windows", "arm64"));
+        assert_eq!(file.kind, FileKind::Archive);
+        assert!(GoProxy::parse_module_version("v0.0.1-go1.21.0").is_none());
+        assert!(GoProxy::parse_module_version("v1.0.0").is_none());
+    }
+
+    #[test]
+    fn test_parse_go_sum() {
+        let text = "21489314
+golang.org/toolchain v0.0.1-go1.21.0.linux-amd64 h1:H7xhdDvIOBxz9TjYVUxhu9cbnPsJqb1V8Wqvhn8VD8s=
+golang.org/toolchain v0.0.1-go1.21.0.linux-amd64/go.mod h1:8wlg68NqwW7eMnI1aABk/C2pCfXj9ZoAHB2/hnE=
+
+go.sum database tree
+";
+        assert_eq!(
+            parse_go_sum(text, "v0.0.1-go1.21.0.linux-amd64").as_deref(),
+            Some("h1:H7xhdDvIOBxz9TjYVUxhu9cbnPsJqb1V8Wqvhn8VD8s=")
+        );
+        assert_eq!(parse_go_sum(text, "v0.0.1-go1.21.1.linux-amd64"), None);
+    }
+
+    #[test]
+    fn test_hash_zip() -> Result<(), anyhow::Error> {
+        let dir = tempfile::tempdir()?;
+        let root = "golang.org/toolchain@v0.0.1-go1.21.0.linux-amd64";
+        // computed with coreutils, the lines are sorted by file name as dirhash.Hash1 does:
+        // for name in VERSION bin/go; do
+        //   printf '%s  %s\n' "$(sha256sum < "$root/$name" | cut -d' ' -f1)" "$root/$name"
+        // done | sha256sum | cut -d' ' -f1 | xxd -r -p | base64
+        for (version, expect) in [
+            // 文件名顺序与哈希顺序相同
+            (
+                "go1.21.0",
+                "h1:IkuO65wb6dYIGVHrbjwUKTFrYh5cep1xHCQgs7rFW9A=",
+            ),
+            // 文件名顺序与哈希顺序不同
+            (
+                "go1.21.0\n",
+                "h1:0Fe2+n15uhF7qCBD0JVi4GoX/CqRRsqCY8p+Qx4r+tk=",
+            ),
+        ] {
+            let path = dir.path().join("v0.0.1-go1.21.0.linux-amd64.zip");
+            let mut zip = ZipWriter::new(std::fs::File::create(&path)?);
+            for (name, content) in [("bin/go", "go"), ("VERSION", version)] {
+                zip.start_file(format!("{root}/{name}"), SimpleFileOptions::default())?;
+                zip.write_all(content.as_bytes())?;
+            }
+            zip.finish()?;
+            assert_eq!(hash_zip(&path)?, expect);
+        }
+        Ok(())
+    }
+}
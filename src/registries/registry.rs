@@ -1,41 +1,85 @@
 use std::{
     env,
     ffi::OsStr,
-    fs::{self, File},
-    io::{BufRead, BufReader, Read, Write},
+    fs,
+    io::{BufRead, BufReader, Write},
     path::Path,
     process::{Command, Stdio},
     time::{Duration, Instant},
 };
 
 use anyhow::anyhow;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar};
 use reqwest::StatusCode;
-use reqwest::blocking::{self, Client};
+use reqwest::blocking::{Client, RequestBuilder};
 use reqwest::header::CONTENT_LENGTH;
-use sha2::{Digest, Sha256};
 use which::which;
 
 use crate::archived::Unpack;
+use crate::cache::{
+    ArchiveCache, CacheEntry, VerifyMethod, compute_file_sha256, is_sha256, verify_file_sha256,
+};
 use crate::consts;
 use crate::dir::Dir;
-
-pub struct Registry<'a> {
-    host: &'a str,
+use crate::progress::Progress;
+use crate::registries::goproxy::{self, GoProxy, TOOLCHAIN_MODULE};
+use crate::registries::registry_index::file_index::file_url_to_path;
+use crate::registries::release::Release;
+use crate::registries::s3::S3Bucket;
+
+pub struct Registry {
+    /// the registry as given, recorded in the download cache.
+    source: String,
+    host: String,
+    /// S3-compatible bucket of `s3+<endpoint>/<bucket>/<prefix>`, requests are signed if any credentials.
+    s3: Option<S3Bucket>,
+    /// GOPROXY of `goproxy+<url>`, archives are the module zips of `golang.org/toolchain`.
+    goproxy: Option<GoProxy>,
     enable_check_archive_size: bool,
     skip_verify: bool,
 }
 
-impl<'a> Registry<'a> {
-    pub fn new(host: &'a str, skip_verify: bool, enable_check_archive_size: bool) -> Self {
-        Self {
+impl Registry {
+    pub fn new(
+        host: &str,
+        skip_verify: bool,
+        enable_check_archive_size: bool,
+    ) -> Result<Self, anyhow::Error> {
+        let source = host;
+        let goproxy = match host {
+            "goproxy" => Some(GoProxy::new(consts::GO_PROXY)),
+            _ => host
+                .strip_prefix("goproxy+")
+                .or_else(|| host.strip_prefix("goproxy|"))
+                .map(GoProxy::new),
+        };
+        let s3_spec = host
+            .strip_prefix("s3+")
+            .or_else(|| host.strip_prefix("s3|"));
+        let (host, s3) = match (&goproxy, s3_spec) {
+            (Some(proxy), _) => (proxy.url().to_owned(), None),
+            (None, Some(spec)) => {
+                let s3 = S3Bucket::parse(spec)?;
+                (s3.base_url(), Some(s3))
+            }
+            (None, None) => (host.trim_end_matches('/').to_owned(), None),
+        };
+        Ok(Self {
+            source: source.to_owned(),
             host,
+            s3,
+            goproxy,
             enable_check_archive_size,
             skip_verify,
-        }
+        })
     }
 
-    pub fn install_go(&self, version: &str) -> Result<(), anyhow::Error> {
+    /// install the go version, the release is used to look up the archive checksum and size if any.
+    pub fn install_go(
+        &self,
+        version: &str,
+        release: Option<&Release>,
+    ) -> Result<(), anyhow::Error> {
         let goup_home = Dir::goup_home()?;
         let version_dest_dir = goup_home.version(version);
 
@@ -53,74 +97,149 @@ impl<'a> Registry<'a> {
             ));
             return Ok(());
         }
-        // download directory
-        let dl_dest_dir = goup_home.cache();
         // 压缩包文件名称
-        let archive_filename = archive_go_version(version);
-        // 压缩包sha256文件名称
-        let archive_sha256_filename = archive_sha256(&archive_filename);
+        let (os, arch) = platform();
+        let module_version = GoProxy::module_version(version, os, arch);
+        let archive_filename = match self.goproxy {
+            // 模块zip统一缓存为 go1.21.0.linux-amd64.zip
+            Some(_) => format!("{version}.{os}-{arch}.zip"),
+            None => archive_go_version(version),
+        };
         // 压缩包url
-        let (archive_url, archive_sha256_url) = archive_url(self.host, &archive_filename);
-        if !dl_dest_dir.exists() {
-            log::debug!("Create download directory");
-            fs::create_dir_all(&dl_dest_dir)?
+        let (archive_url, archive_sha256_url) = match self.goproxy {
+            Some(ref proxy) => (proxy.archive_url(&module_version), String::new()),
+            None => archive_url(&self.host, &archive_filename),
+        };
+        let cache = ArchiveCache::open()?;
+        // 索引中的压缩包文件信息
+        let go_file = release.and_then(|v| v.file(&archive_filename));
+        if go_file.is_none() && release.and_then(|v| v.has_archive(os, arch)) == Some(false) {
+            return Err(anyhow!(
+                "no binary release of {version} for {os}/{arch} in registry index"
+            ));
         }
 
-        // 压缩包文件
-        let archive_file = dl_dest_dir.join_path(archive_filename);
-        let archive_sha256_file = dl_dest_dir.join_path(archive_sha256_filename);
-        if !archive_file.exists() {
-            pb.set_message(format!(
-                "Downloading archive file from {} to {}",
-                archive_url,
-                archive_file.display()
+        // 期望的sha256, 优先使用索引中的校验和或同一来源已校验过的缓存, 否则从仓库获取.
+        let expect_sha256 = if self.skip_verify {
+            None
+        } else if self.goproxy.is_some() {
+            // 模块zip下载后按go.sum的h1校验
+            None
+        } else if let Some(go_file) = go_file
+            && !go_file.sha256.is_empty()
+        {
+            Some(go_file.sha256.to_ascii_lowercase())
+        } else if let Some(entry) = cache.find_by_url(&archive_url)?
+            && let Ok(sha256) = fs::read_to_string(entry.sha256_file())
+        {
+            Some(sha256.trim().to_owned())
+        } else if consts::offline()
+            && let Some(entry) = cache
+                .entries()?
+                .into_iter()
+                .filter(|v| v.meta.filename == archive_filename)
+                .max_by_key(|v| v.meta.accessed_at)
+        {
+            // 离线时使用同名缓存, 未校验过的需要联网获取sha256
+            if entry.meta.verify == VerifyMethod::Unverified {
+                consts::ensure_online(format!(
+                    "verifying the unverified cached {archive_filename}, try add option '--skip-verify'"
+                ))?;
+            }
+            Some(entry.meta.sha256)
+        } else {
+            consts::ensure_online(format!("fetching archive sha256 of {version}"))?;
+            pb.set_message(format!("Fetching archive sha256 from {archive_sha256_url}"));
+            let sha256 = self.fetch_text(&archive_sha256_url).inspect_err(|_| {
+                log::warn!(
+                    "Download archive sha256 file failure, maybe the version '{version}' miss it, try add option '--skip-verify'",
+                );
+            })?;
+            Some(sha256.trim().to_ascii_lowercase())
+        };
+        // 来自网络的sha256同时也是缓存目录名, 必须是64位小写十六进制
+        if let Some(ref sha256) = expect_sha256
+            && !is_sha256(sha256)
+        {
+            return Err(anyhow!(
+                "invalid SHA-256 '{sha256}' of {archive_filename}, try add option '--skip-verify'"
             ));
-            // 下载压缩包文件
-            Self::download_file(&archive_file, &archive_url, Some(&mp))?;
-
-            //  有一些镜像仓库不支持获取压缩包长度, 默认不验证
-            if self.enable_check_archive_size {
-                log::debug!("Check archive file content length");
-                // 压缩包长度
-                let archive_content_length =
-                    Self::get_archive_content_length(version, &archive_url)?;
-                // 检查大小
-                let got_archive_content_length = archive_file.metadata()?.len();
-                if got_archive_content_length != archive_content_length {
-                    return Err(anyhow!(
-                        "downloaded file {} size {} doesn't match server size {}",
-                        archive_file.display(),
-                        got_archive_content_length,
-                        archive_content_length,
+        }
+        // 缓存的压缩包, 校验时按sha256查找(不同的来源可共享), 否则按来源查找
+        let cached = match expect_sha256 {
+            Some(ref sha256) => match cache.get(sha256) {
+                Some(entry) => {
+                    pb.set_message(format!(
+                        "Verifying '{}' sha256",
+                        entry.archive_file().display()
                     ));
+                    if let Err(e) = verify_file_sha256(entry.archive_file(), sha256, &mp) {
+                        log::warn!("{e}, remove it and download again");
+                        cache.remove(&entry)?;
+                        None
+                    } else {
+                        Some(entry)
+                    }
                 }
-            }
-        }
-        if self.skip_verify {
-            pb.set_message("Skip verify archive file sha256");
+                None => None,
+            },
+            None => cache.find_by_url(&archive_url)?,
+        };
+        let mut entry = if let Some(entry) = cached {
+            entry
         } else {
-            if !archive_sha256_file.exists()
-                || Self::verify_archive_file_sha256(&archive_file, &archive_sha256_file).is_err()
+            consts::ensure_online(format!(
+                "{version} is not installed and {archive_filename} is not in the download cache, downloading it"
+            ))?;
+            //  有一些镜像仓库不支持获取压缩包长度, 默认不验证
+            let expect_size = if !self.enable_check_archive_size {
+                None
+            } else if let Some(go_file) = go_file
+                && go_file.size > 0
             {
-                // 下载压缩包sha256
-                pb.set_message(format!(
-                    "Download archive sha256 file from {} to {}",
-                    archive_sha256_url,
-                    archive_sha256_file.display()
-                ));
-                // 下载压缩包sha256文件
-                let r = Self::download_file(&archive_sha256_file, &archive_sha256_url, None);
-                if r.is_err() {
+                Some(go_file.size)
+            } else {
+                log::debug!("Check archive file content length");
+                Some(self.get_archive_content_length(version, &archive_url)?)
+            };
+            pb.set_message(format!("Downloading archive file from {archive_url}"));
+            self.download_archive(
+                &cache,
+                &archive_url,
+                &archive_filename,
+                expect_sha256.as_deref(),
+                expect_size,
+                &mp,
+            )?
+        };
+        if let Some(ref expect_sha256) = expect_sha256 {
+            cache.set_verified(&mut entry, expect_sha256)?;
+        } else if let Some(ref proxy) = self.goproxy
+            && !self.skip_verify
+        {
+            pb.set_message(format!("Verifying {module_version} h1 hash"));
+            // 已按go.sum校验过的缓存, 复用记录的h1, 不再查询
+            let expect_h1 = match entry.recorded_h1() {
+                Some(h1) => h1,
+                None => proxy.lookup_h1(&module_version).inspect_err(|_| {
                     log::warn!(
-                        "Download archive sha256 file failure, maybe the version '{version}' miss it, try add option '--skip-verify'",
+                        "Lookup h1 hash of '{module_version}' failure, try add option '--skip-verify'",
                     );
-                    return r;
-                }
+                })?,
+            };
+            let got_h1 = goproxy::hash_zip(entry.archive_file())?;
+            if got_h1 != expect_h1 {
+                cache.remove(&entry)?;
+                return Err(anyhow!(
+                    "{archive_url} corrupt? does not have expected h1 hash of {expect_h1}, got {got_h1}",
+                ));
             }
-            // 校验压缩包sha256
-            pb.set_message(format!("Verifying '{}' sha256", archive_file.display()));
-            Self::verify_archive_file_sha256(&archive_file, &archive_sha256_file)?;
+            cache.set_verified_by(&mut entry, VerifyMethod::GoSum, &expect_h1)?;
+        } else {
+            pb.set_message("Skip verify archive file sha256");
         }
+        cache.touch(&mut entry)?;
+        let archive_file = entry.archive_file();
 
         // 解压
         pb.set_message(format!(
@@ -132,10 +251,11 @@ impl<'a> Registry<'a> {
             log::debug!("Create version directory: {}", version_dest_dir.display());
             fs::create_dir_all(&version_dest_dir)?
         }
-        archive_file
-            .to_string_lossy()
-            .parse::<Unpack>()?
-            .unpack(&version_dest_dir, &archive_file)?;
+        archive_file.to_string_lossy().parse::<Unpack>()?.unpack(
+            &version_dest_dir,
+            &archive_file,
+            &mp,
+        )?;
         // 设置解压成功标记
         goup_home.create_dot_unpacked_success_file(version)?;
         pb.finish_with_message(format!(
@@ -147,12 +267,149 @@ impl<'a> Registry<'a> {
         Ok(())
     }
 
+    /// download_archive 下载压缩包文件到缓存, 校验大小和sha256(如果有).
+    pub fn download_archive(
+        &self,
+        cache: &ArchiveCache,
+        archive_url: &str,
+        archive_filename: &str,
+        expect_sha256: Option<&str>,
+        expect_size: Option<u64>,
+        mp: &MultiProgress,
+    ) -> Result<CacheEntry, anyhow::Error> {
+        let tmp_file = cache.tmp_file(archive_filename)?;
+        // 下载压缩包文件
+        self.download_file(&tmp_file, archive_url, mp)?;
+        // 检查大小
+        let got_archive_content_length = tmp_file.metadata()?.len();
+        if let Some(expect_size) = expect_size
+            && got_archive_content_length != expect_size
+        {
+            fs::remove_file(&tmp_file)?;
+            return Err(anyhow!(
+                "downloaded file {} size {} doesn't match server size {}",
+                tmp_file.display(),
+                got_archive_content_length,
+                expect_size,
+            ));
+        }
+        // 校验压缩包sha256
+        let sha256 = compute_file_sha256(&tmp_file, mp)?;
+        if let Some(expect_sha256) = expect_sha256
+            && expect_sha256 != sha256
+        {
+            fs::remove_file(&tmp_file)?;
+            return Err(anyhow!(
+                "{} corrupt? does not have expected SHA-256 of {}",
+                archive_url,
+                expect_sha256,
+            ));
+        }
+        cache.insert(
+            &tmp_file,
+            &sha256,
+            archive_filename,
+            Some(archive_url),
+            Some(&self.source),
+            VerifyMethod::Unverified,
+        )
+    }
+
+    /// download the cached archive file again from its source url, verified by the sha256 of
+    /// the index, the recorded sha256 file or the sha256 file of the url, or by the `h1:` hash
+    /// of go.sum for a GOPROXY. a corrupt archive file without any of them expects the same
+    /// content as the first download.
+    pub fn redownload_archive(
+        &self,
+        cache: &ArchiveCache,
+        entry: &CacheEntry,
+        index_sha256: Option<&str>,
+        corrupt: bool,
+        mp: &MultiProgress,
+    ) -> Result<CacheEntry, anyhow::Error> {
+        let Some(ref url) = entry.meta.url else {
+            return Err(anyhow!("unknown source url"));
+        };
+        if let Some(ref proxy) = self.goproxy {
+            let (_, module_version) = GoProxy::split_archive_url(url)
+                .ok_or_else(|| anyhow!("{url} is not a module zip of {TOOLCHAIN_MODULE}"))?;
+            let expect_h1 = match entry.recorded_h1() {
+                Some(h1) => h1,
+                None => proxy.lookup_h1(module_version)?,
+            };
+            log::info!("Redownloading {url}");
+            let mut fresh =
+                self.download_archive(cache, url, &entry.meta.filename, None, None, mp)?;
+            let got_h1 = goproxy::hash_zip(fresh.archive_file())?;
+            if got_h1 != expect_h1 {
+                if fresh.meta.sha256 != entry.meta.sha256 {
+                    cache.remove(&fresh)?;
+                }
+                return Err(anyhow!(
+                    "{url} corrupt? does not have expected h1 hash of {expect_h1}, got {got_h1}",
+                ));
+            }
+            if fresh.meta.sha256 != entry.meta.sha256 {
+                cache.remove(entry)?;
+            }
+            cache.set_verified_by(&mut fresh, VerifyMethod::GoSum, &expect_h1)?;
+            return Ok(fresh);
+        }
+
+        let recorded = fs::read_to_string(entry.sha256_file())
+            .ok()
+            .map(|v| v.trim().to_owned());
+        let expect = index_sha256
+            .map(ToOwned::to_owned)
+            .or(recorded)
+            .or_else(|| {
+                let sha256_url = format!("{url}.sha256");
+                self.fetch_text(&sha256_url)
+                    .inspect_err(|e| log::debug!("Fetch {sha256_url} failure: {e}"))
+                    .ok()
+                    .map(|v| v.trim().to_ascii_lowercase())
+            });
+        let is_verified = expect.is_some();
+        let expect = match expect {
+            Some(expect) => expect,
+            None if corrupt => entry.meta.sha256.clone(),
+            None => return Err(anyhow!("no sha256 to verify the download of {url}")),
+        };
+
+        log::info!("Redownloading {url}");
+        let mut fresh =
+            self.download_archive(cache, url, &entry.meta.filename, Some(&expect), None, mp)?;
+        if fresh.meta.sha256 != entry.meta.sha256 {
+            cache.remove(entry)?;
+        }
+        if is_verified {
+            cache.set_verified(&mut fresh, &expect)?;
+        }
+        Ok(fresh)
+    }
+
     // get_archive_content_length 获取压缩包文件长度
-    fn get_archive_content_length(version: &str, archive_url: &str) -> Result<u64, anyhow::Error> {
-        let resp = blocking::Client::builder()
-            .build()?
-            .head(archive_url)
-            .send()?;
+    fn get_archive_content_length(
+        &self,
+        version: &str,
+        archive_url: &str,
+    ) -> Result<u64, anyhow::Error> {
+        if archive_url.starts_with("file://") {
+            return file_url_to_path(archive_url)?
+                .metadata()
+                .map(|v| v.len())
+                .map_err(|_| {
+                    anyhow!(
+                        "no binary release of {} for {}/{} at {}",
+                        version,
+                        env::consts::OS,
+                        env::consts::ARCH,
+                        archive_url,
+                    )
+                });
+        }
+        consts::ensure_online(format!("checking size of {archive_url}"))?;
+        let resp = self.head(&Client::new(), archive_url)?.send()?;
         if resp.status() == StatusCode::NOT_FOUND {
             return Err(anyhow!(
                 "no binary release of {} for {}/{} at {}",
@@ -179,120 +436,112 @@ impl<'a> Registry<'a> {
 
     /// download_file 下载文件
     fn download_file<P: AsRef<Path>>(
+        &self,
         dest: P,
         url: &str,
-        mp: Option<&MultiProgress>,
+        mp: &MultiProgress,
     ) -> Result<(), anyhow::Error> {
-        if let Some(mp) = mp {
-            let client = Client::new();
-            let content_length = client
-                .head(url)
-                .header("User-Agent", "goup-rs Client")
-                .timeout(Duration::from_secs(10))
-                .send()?
-                .headers()
-                .get(CONTENT_LENGTH)
-                .ok_or_else(|| anyhow!("no content length header"))?
-                .to_str()?
-                .parse::<u64>()?;
-            let mut dest_file = fs::File::create(dest)?;
-
-            let pb = mp.add(ProgressBar::new(content_length));
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template(
-                        "  [{elapsed_precise}] [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({eta})",
-                    )?
-                    .progress_chars("=> "),
-            );
-            pb.enable_steady_tick(Duration::from_millis(100));
-
-            const CHUNK_SIZE: u64 = 1024 * 1024; // 1MB
-            const MAX_CHUNK_SIZE: u64 = 1024 * 1024 * 16; // 16MB
-
-            let mut speed = 0.0;
-            let mut chunk_size = 2 * CHUNK_SIZE;
-            let mut start = 0;
-            while start < content_length {
-                let end = start + chunk_size - 1;
-                let instant = Instant::now();
-                let buf = client
-                    .get(url)
-                    .header("User-Agent", "GOUP Client")
-                    .header("Range", format!("bytes={start}-{end}"))
-                    .timeout(Duration::from_secs(30))
-                    .send()?
-                    .bytes()?;
-                let elapsed = instant.elapsed();
-                dest_file.write_all(&buf)?;
-
-                let real_chunk_size = buf.len() as u64;
-                let real_speed = (real_chunk_size as f32) / elapsed.as_secs_f32();
-
-                start = end + 1;
-                speed = if speed == 0.0 {
-                    real_speed
-                } else {
-                    (speed + real_speed) * 0.5
-                };
-                chunk_size = if speed < real_speed {
-                    chunk_size as f32 * 1.75
-                } else {
-                    chunk_size as f32 * 0.75
-                } as u64;
-                chunk_size = chunk_size.clamp(CHUNK_SIZE, MAX_CHUNK_SIZE);
-
-                pb.inc(real_chunk_size);
+        // 本地仓库, 优先硬链接, 跨文件系统时复制
+        if url.starts_with("file://") {
+            let src = file_url_to_path(url)?;
+            let dest = dest.as_ref();
+            if dest.exists() {
+                fs::remove_file(dest)?;
             }
-
-            pb.finish_and_clear();
-            mp.remove(&pb);
-        } else {
-            let mut response = blocking::get(url)?;
-            if !response.status().is_success() {
-                return Err(anyhow!("Downloading file failure"));
+            if fs::hard_link(&src, dest).is_err() {
+                fs::copy(&src, dest).map_err(|e| anyhow!("copy {} failed: {e}", src.display()))?;
             }
-            let mut file = File::create(dest)?;
-            response.copy_to(&mut file)?;
+            return Ok(());
         }
+        consts::ensure_online(format!("downloading {url}"))?;
+        let client = Client::new();
+        let content_length = self
+            .head(&client, url)?
+            .header("User-Agent", "goup-rs Client")
+            .timeout(Duration::from_secs(10))
+            .send()?
+            .headers()
+            .get(CONTENT_LENGTH)
+            .ok_or_else(|| anyhow!("no content length header"))?
+            .to_str()?
+            .parse::<u64>()?;
+        let mut dest_file = fs::File::create(dest)?;
+
+        let progress = Progress::bytes(mp, content_length, format!("Downloading {url}"));
+
+        const CHUNK_SIZE: u64 = 1024 * 1024; // 1MB
+        const MAX_CHUNK_SIZE: u64 = 1024 * 1024 * 16; // 16MB
+
+        let mut speed = 0.0;
+        let mut chunk_size = 2 * CHUNK_SIZE;
+        let mut start = 0;
+        while start < content_length {
+            let end = start + chunk_size - 1;
+            let instant = Instant::now();
+            let buf = self
+                .get(&client, url)?
+                .header("User-Agent", "GOUP Client")
+                .header("Range", format!("bytes={start}-{end}"))
+                .timeout(Duration::from_secs(30))
+                .send()?
+                .bytes()?;
+            let elapsed = instant.elapsed();
+            dest_file.write_all(&buf)?;
+
+            let real_chunk_size = buf.len() as u64;
+            let real_speed = (real_chunk_size as f32) / elapsed.as_secs_f32();
+
+            start = end + 1;
+            speed = if speed == 0.0 {
+                real_speed
+            } else {
+                (speed + real_speed) * 0.5
+            };
+            chunk_size = if speed < real_speed {
+                chunk_size as f32 * 1.75
+            } else {
+                chunk_size as f32 * 0.75
+            } as u64;
+            chunk_size = chunk_size.clamp(CHUNK_SIZE, MAX_CHUNK_SIZE);
+
+            progress.inc(real_chunk_size);
+        }
+
+        progress.finish();
         Ok(())
     }
 
-    /// compute_file_sha256 计算文件的sha256
-    fn compute_file_sha256<P: AsRef<Path>>(path: P) -> Result<String, anyhow::Error> {
-        let mut context = Sha256::new();
-        let mut file = File::open(path)?;
-        let mut buffer = [0; 4096]; // 定义一个缓冲区来处理字节流数据
-        loop {
-            let bytes_read = file.read(&mut buffer)?;
-            if bytes_read == 0 {
-                break;
-            }
-            context.update(&buffer[..bytes_read]);
+    /// fetch_text 获取文本内容, 如sha256文件
+    pub fn fetch_text(&self, url: &str) -> Result<String, anyhow::Error> {
+        if url.starts_with("file://") {
+            return fs::read_to_string(file_url_to_path(url)?)
+                .map_err(|e| anyhow!("Reading {url} failure, {e}"));
         }
-        Ok(hex::encode(context.finalize()))
-    }
-
-    /// verify_archive_file_sha256 校验文件sha256是否合法
-    fn verify_archive_file_sha256<P1, P2>(
-        archive_file: P1,
-        archive_sha256_file: P2,
-    ) -> Result<(), anyhow::Error>
-    where
-        P1: AsRef<Path>,
-        P2: AsRef<Path>,
-    {
-        let expect_sha256 = fs::read_to_string(archive_sha256_file)?;
-        let expect_sha256 = expect_sha256.trim();
-        let computed_sha256 = Self::compute_file_sha256(&archive_file)?;
-        if computed_sha256 != expect_sha256 {
+        consts::ensure_online(format!("fetching {url}"))?;
+        let response = self.get(&Client::new(), url)?.send()?;
+        if !response.status().is_success() {
             return Err(anyhow!(
-                "{} corrupt? does not have expected SHA-256 of {}",
-                archive_file.as_ref().display(),
-                expect_sha256,
+                "Fetching {url} failure, status {}",
+                response.status()
             ));
         }
-        Ok(())
+        Ok(response.text()?)
+    }
+
+    /// get 构建GET请求, S3仓库按需签名
+    fn get(&self, client: &Client, url: &str) -> Result<RequestBuilder, anyhow::Error> {
+        match self.s3 {
+            Some(ref s3) => s3.get(client, url),
+            None => Ok(client.get(url)),
+        }
+    }
+
+    /// head 构建HEAD请求, S3仓库按需签名
+    fn head(&self, client: &Client, url: &str) -> Result<RequestBuilder, anyhow::Error> {
+        match self.s3 {
+            Some(ref s3) => s3.head(client, url),
+            None => Ok(client.head(url)),
+        }
     }
 }
 
@@ -305,6 +554,7 @@ impl<'a> NightlyRegistry<'a> {
         Self { _cl }
     }
     pub fn install_go(&self) -> Result<(), anyhow::Error> {
+        consts::ensure_online("building gotip from the go development tree")?;
         if which("git").is_err() {
             return Err(anyhow!(
                 r#""git" binary not found, make sure it is installed!"#,
@@ -393,9 +643,9 @@ impl<'a> NightlyRegistry<'a> {
     }
 }
 
-/// archive_go_version returns the zip or tar.gz of the given Go version.
-/// go1.21.5.linux-amd64.tar.gz, go1.21.5.windows-amd64.zip
-fn archive_go_version(version: &str) -> String {
+/// platform returns the os and arch of the Go archive file for the current platform.
+/// (linux, amd64), (darwin, arm64), (windows, 386)
+pub(crate) fn platform() -> (&'static str, &'static str) {
     let os = match env::consts::OS {
         "macos" => "darwin",
         os => os,
@@ -407,10 +657,30 @@ fn archive_go_version(version: &str) -> String {
         (_, "aarch64") => "arm64",
         _ => env::consts::ARCH,
     };
+    (os, arch)
+}
+
+/// archive_go_version returns the zip or tar.gz of the given Go version.
+/// go1.21.5.linux-amd64.tar.gz, go1.21.5.windows-amd64.zip
+fn archive_go_version(version: &str) -> String {
+    let (os, arch) = platform();
     let ext = if os == "windows" { "zip" } else { "tar.gz" };
     format!("{version}.{os}-{arch}.{ext}")
 }
 
+/// parse_archive_filename returns the version, os and arch of the given archive filename.
+/// go1.21.5.linux-amd64.tar.gz -> (1.21.5, linux, amd64)
+/// go1.21.5.src.tar.gz         -> None
+pub(crate) fn parse_archive_filename(archive_filename: &str) -> Option<(&str, &str, &str)> {
+    let name = archive_filename.strip_prefix("go")?;
+    let name = [".tar.gz", ".zip", ".pkg", ".msi"]
+        .into_iter()
+        .find_map(|ext| name.strip_suffix(ext))?;
+    let (version, platform) = name.rsplit_once('.')?;
+    let (os, arch) = platform.split_once('-')?;
+    (!version.is_empty() && !os.is_empty() && !arch.is_empty()).then_some((version, os, arch))
+}
+
 /// archive_sha256 returns `{archive}.sha256`
 /// go1.21.5.linux-amd64.tar.gz.sha256, go1.21.5.windows-amd64.zip.sha256
 #[inline]
@@ -423,13 +693,13 @@ fn archive_sha256(archive_filename: &str) -> String {
 fn archive_url(registry: &str, archive_filename: &str) -> (String, String) {
     (
         format!("{registry}/{archive_filename}"),
-        format!("{registry}/{archive_filename}.sha256"),
+        format!("{registry}/{}", archive_sha256(archive_filename)),
     )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{archive_go_version, archive_sha256, archive_url};
+    use super::{archive_go_version, archive_sha256, archive_url, parse_archive_filename};
 
     #[test]
     fn test_archive() {
@@ -478,4 +748,26 @@ mod tests {
         assert!(archive_url.starts_with(&format!("{registry}/{TEST_VERSION}")));
         assert!(archive_sha256_url.starts_with(&format!("{registry}/{TEST_VERSION}")));
     }
+
+    #[test]
+    fn test_parse_archive_filename() {
+        assert_eq!(
+            parse_archive_filename("go1.21.5.linux-amd64.tar.gz"),
+            Some(("1.21.5", "linux", "amd64"))
+        );
+        assert_eq!(
+            parse_archive_filename("go1.22rc1.windows-386.zip"),
+            Some(("1.22rc1", "windows", "386"))
+        );
+        assert_eq!(
+            parse_archive_filename("go1.21.5.darwin-arm64.pkg"),
+            Some(("1.21.5", "darwin", "arm64"))
+        );
+        assert_eq!(parse_archive_filename("go1.21.5.src.tar.gz"), None);
+        assert_eq!(
+            parse_archive_filename("go1.21.5.linux-amd64.tar.gz.sha256"),
+            None
+        );
+        assert_eq!(parse_archive_filename("README"), None);
+    }
 }
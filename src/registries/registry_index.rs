@@ -1,67 +1,127 @@
+pub mod cached_index;
+pub mod file_index;
+mod goproxy_index;
+pub mod html_index;
+mod json_index;
+pub mod multi_index;
 pub mod ngx_auto_index;
 pub mod ngx_fancy_index;
 mod official;
 mod official_git;
+pub mod s3_index;
 
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::str::FromStr;
 
 use anyhow::anyhow;
+use jiff::civil::{Date, DateTime};
+use jiff::{Timestamp, tz::TimeZone};
 use regex::Regex;
 use semver::VersionReq;
 
 use self::official::Official;
 
+use crate::command::utils::ByteSize;
 use crate::consts;
-use crate::registries::go_index::{GoIndex, Resolution};
+use crate::registries::go_index::{GoIndex, Resolution, Validators};
+use crate::registries::registry_index::file_index::FileIndex;
+use crate::registries::registry_index::goproxy_index::GoProxyIndex;
+use crate::registries::registry_index::html_index::HtmlIndex;
+use crate::registries::registry_index::json_index::JsonIndex;
 use crate::registries::registry_index::ngx_auto_index::NgxAutoIndex;
 use crate::registries::registry_index::ngx_fancy_index::NgxFancyIndex;
 use crate::registries::registry_index::official_git::OfficialGit;
+use crate::registries::registry_index::s3_index::S3Index;
+use crate::registries::release::Release;
 use crate::{toolchain, toolchain::ToolchainFilter};
 
+/// answer of a conditional fetch.
+#[derive(Debug)]
+pub enum Fetched {
+    Modified(Vec<Release>, Validators),
+    NotModified,
+}
+
 pub trait RegistryIndex {
+    /// list upstream go releases, sorted by version.
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error>;
+    /// list upstream go releases if modified since the validators.
+    /// the index without conditional request support always fetches.
+    fn list_upstream_releases_if_modified(
+        &self,
+        _validators: &Validators,
+    ) -> Result<Fetched, anyhow::Error> {
+        Ok(Fetched::Modified(
+            self.list_upstream_releases()?,
+            Validators::default(),
+        ))
+    }
     /// get upstream latest go version.
-    fn get_upstream_latest_go_version(&self) -> Result<String, anyhow::Error>;
+    fn get_upstream_latest_go_version(&self) -> Result<String, anyhow::Error> {
+        self.list_upstream_releases()?
+            .into_iter()
+            .rev()
+            .find(|v| v.stable)
+            .map(|v| v.version)
+            .ok_or_else(|| anyhow!("Getting latest Go version failed"))
+    }
+    /// the local index of the registry index, if cached.
+    fn local_index(&self) -> Option<GoIndex> {
+        None
+    }
     /// list upstream go versions.
-    fn list_upstream_go_versions(&self) -> Result<Vec<String>, anyhow::Error>;
+    fn list_upstream_go_versions(&self) -> Result<Vec<String>, anyhow::Error> {
+        Ok(self
+            .list_upstream_releases()?
+            .into_iter()
+            .map(|v| v.version)
+            .collect())
+    }
+    /// list sha256 of the upstream archive files, keyed by archive filename.
+    /// empty if the index does not provide checksums.
+    fn list_upstream_archive_sha256(&self) -> Result<HashMap<String, String>, anyhow::Error> {
+        Ok(self
+            .list_upstream_releases()?
+            .into_iter()
+            .flat_map(|v| v.files)
+            .filter(|v| !v.sha256.is_empty())
+            .map(|v| (v.filename, v.sha256))
+            .collect())
+    }
 
-    /// match version request.
+    /// match release request.
     /// 1. 尝试先从本地缓存查找, 如果找到, 且是确定的归档版本, 则返回, 否则从上游查找.
-    fn match_version_req(&self, version_req: &str) -> Result<String, anyhow::Error> {
+    fn match_release_req(&self, version_req: &str) -> Result<Release, anyhow::Error> {
         log::debug!("version request: {version_req}");
         let ver_req = VersionReq::parse(version_req)?;
 
-        let search_type = GoIndex::read().map_or(Ok(Resolution::Unresolved), |v| {
+        let search_type = self.local_index().map_or(Ok(Resolution::Unresolved), |v| {
             v.try_match_archived_version(&ver_req)
         })?;
-        if let Resolution::Resolved(ver) = search_type {
+        if let Resolution::Resolved(release) = search_type {
             log::debug!("use archived!!!");
-            Ok(ver)
+            Ok(release)
         } else {
             log::debug!("use active!!!");
-            self.list_upstream_go_versions_filter(None)?
-                .iter()
+            self.list_upstream_releases_filter(None)?
+                .into_iter()
                 .rev()
-                .find_map(|v| {
-                    toolchain::semantic(v)
-                        .ok()
-                        .filter(|semver| ver_req.matches(semver))
-                        .map(|_| v)
+                .find(|v| {
+                    toolchain::semantic(&v.version).is_ok_and(|semver| ver_req.matches(&semver))
                 })
-                .map(|v| v.to_owned())
                 .ok_or_else(|| anyhow!("no matching version found!"))
         }
     }
-    /// list upstream go versions filter by toolchain filter.
-    /// NOTE: 此方法每次都从上游查找, 并尝试更新本地缓存!
-    fn list_upstream_go_versions_filter(
+    /// list upstream go releases filter by toolchain filter.
+    /// NOTE: 缓存的索引在有效期内不会从上游查找!
+    fn list_upstream_releases_filter(
         &self,
         filter: Option<&ToolchainFilter>,
-    ) -> Result<Vec<String>, anyhow::Error> {
-        let ver = self.list_upstream_go_versions()?;
-        GoIndex::write_if_change(&ver.clone().into()).ok();
+    ) -> Result<Vec<Release>, anyhow::Error> {
+        let releases = self.list_upstream_releases()?;
         let Some(filter) = filter else {
-            return Ok(ver);
+            return Ok(releases);
         };
         let re = match filter {
             ToolchainFilter::Stable => {
@@ -78,19 +138,61 @@ pub trait RegistryIndex {
             ToolchainFilter::Filter(s) => format!("(.*{s}.*)"),
         };
         let re = Regex::new(&re)?;
-        Ok(ver
+        Ok(releases
             .into_iter()
-            .filter_map(|v| re.is_match(&v).then_some(v))
+            .filter(|v| re.is_match(&v.version))
+            .collect())
+    }
+    /// list upstream go versions filter by toolchain filter.
+    fn list_upstream_go_versions_filter(
+        &self,
+        filter: Option<&ToolchainFilter>,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        Ok(self
+            .list_upstream_releases_filter(filter)?
+            .into_iter()
+            .map(|v| v.version)
             .collect())
     }
 }
 
+/// parse the size column of a directory listing, such as `68765432`, `66M` or `65.6 MiB`.
+/// 0 if unknown, such as `-`.
+fn parse_listing_size(s: &str) -> u64 {
+    s.parse::<ByteSize>().map_or(0, |v| v.0)
+}
+
+/// parse the date column of a directory listing, such as `07-May-2024 21:12`, `2024-May-07 21:12`
+/// `2024-05-07T21:12:00Z` or `2024-05-07`.
+fn parse_listing_date(s: &str) -> Option<Date> {
+    let s = s.trim();
+    if let Ok(ts) = s.parse::<Timestamp>() {
+        return Some(ts.to_zoned(TimeZone::UTC).date());
+    }
+    [
+        "%d-%b-%Y %H:%M",
+        "%Y-%b-%d %H:%M",
+        "%Y-%b-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%d %H:%M:%S",
+    ]
+    .into_iter()
+    .find_map(|format| DateTime::strptime(format, s).ok())
+    .map(|v| v.date())
+    .or_else(|| s.parse::<Date>().ok())
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum RegistryIndexType {
     Official(String),
     OfficialGit(String),
     NgxAutoIndex(String),
     NgxFancyIndex(String),
+    Html(String),
+    Json(String),
+    File(String),
+    S3(String),
+    GoProxy(String),
 }
 impl RegistryIndexType {
     pub fn as_registry_index(&self) -> Box<dyn RegistryIndex> {
@@ -99,37 +201,291 @@ impl RegistryIndexType {
             Self::OfficialGit(url) => Box::new(OfficialGit::new(url)),
             Self::NgxAutoIndex(host) => Box::new(NgxAutoIndex::new(host)),
             Self::NgxFancyIndex(host) => Box::new(NgxFancyIndex::new(host)),
+            Self::Html(url) => Box::new(HtmlIndex::new(url)),
+            Self::Json(url) => Box::new(JsonIndex::new(url)),
+            Self::S3(spec) => Box::new(S3Index::new(spec)),
+            Self::File(url) => Box::new(FileIndex::new(url)),
+            Self::GoProxy(url) => Box::new(GoProxyIndex::new(url)),
+        }
+    }
+
+    /// new from the type name, the canonical or legacy name.
+    fn with_type(t: &str, url: &str) -> Option<Self> {
+        let url = url.to_owned();
+        match t {
+            "official" => Some(Self::Official(url)),
+            "git" => Some(Self::OfficialGit(url)),
+            "nginx-auto" | "ngx-auto-index" => Some(Self::NgxAutoIndex(url)),
+            "nginx-fancy" | "ngx-fancy-index" => Some(Self::NgxFancyIndex(url)),
+            "html" => Some(Self::Html(url)),
+            "json" => Some(Self::Json(url)),
+            "s3" => Some(Self::S3(url)),
+            "goproxy" => Some(Self::GoProxy(url)),
+            "file" => Some(Self::File(url)),
+            _ => None,
+        }
+    }
+}
+
+/// registry index types, the canonical name of `<type>+<url>` and the legacy names of `type|host`.
+const INDEX_TYPES: [(&str, &[&str]); 9] = [
+    ("official", &[]),
+    ("git", &[]),
+    ("nginx-auto", &["ngx-auto-index"]),
+    ("nginx-fancy", &["ngx-fancy-index"]),
+    ("html", &[]),
+    ("json", &[]),
+    ("s3", &[]),
+    ("goproxy", &[]),
+    ("file", &[]),
+];
+
+/// split several registry indexes separated by comma. a comma separates only if another
+/// registry index follows, so a comma of the url is kept, such as `html+...#pattern=a{1,2}`.
+pub fn split_registry_indexes(s: &str) -> Vec<&str> {
+    let mut indexes = Vec::new();
+    let mut start = 0;
+    for (i, _) in s.match_indices(',') {
+        if is_registry_index_start(&s[i + 1..]) {
+            indexes.push(&s[start..i]);
+            start = i + 1;
+        }
+    }
+    indexes.push(&s[start..]);
+    indexes
+}
+
+/// whether a registry index starts here: a keyword, a url, `<type>+` or `type|` of a known type,
+/// or `<word>+<scheme>://` of an unknown type, which is reported when parsing.
+fn is_registry_index_start(s: &str) -> bool {
+    let s = s.trim_start();
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(s.len());
+    let (word, rest) = s.split_at(end);
+    if word.is_empty() {
+        return false;
+    }
+    let is_type = INDEX_TYPES
+        .iter()
+        .any(|(name, legacy)| *name == word || legacy.contains(&word));
+    match rest.chars().next() {
+        None | Some(',') => ["official", "git", "goproxy"].contains(&word),
+        Some(':') => rest.starts_with("://") && ["http", "https", "file"].contains(&word),
+        Some('+' | '|') => {
+            is_type
+                || rest[1..].split_once("://").is_some_and(|(scheme, _)| {
+                    !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric())
+                })
         }
+        _ => false,
     }
 }
 
+/// error of unknown registry index type, suggest the closest type if any.
+fn unknown_index_type(t: &str, s: &str) -> anyhow::Error {
+    let suggestion = INDEX_TYPES
+        .iter()
+        .flat_map(|(name, legacy)| {
+            std::iter::once(name)
+                .chain(legacy.iter())
+                .map(move |v| (*name, edit_distance(t, v)))
+        })
+        .min_by_key(|v| v.1)
+        .filter(|v| v.1 <= 3)
+        .map(|v| format!(" Did you mean `{}`?", v.0))
+        .unwrap_or_default();
+    let types = INDEX_TYPES.map(|v| v.0).join(", ");
+    anyhow!("unknown registry index type `{t}` of '{s}'.{suggestion} Supported types: {types}.")
+}
+
+/// levenshtein distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            cur[j + 1] = (prev[j] + usize::from(ca != *cb))
+                .min(prev[j + 1] + 1)
+                .min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
 impl FromStr for RegistryIndexType {
     type Err = anyhow::Error;
 
+    /// parse the registry index:
+    /// - `<type>+<url>`, such as `official+https://go.dev`, `nginx-auto+https://mirrors.example.com/golang/`.
+    /// - `type|host` of the legacy syntax, such as `ngx-fancy-index|https://mirrors.example.com/golang/`.
+    /// - a bare `https://` url of the official index, a `file://` url of the local directory.
+    /// - `official`, `git` and `goproxy` of the default url.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (t, host) = match s {
-            "git" => ("git", consts::GO_SOURCE_GIT_URL),
+        let s = s.trim();
+        let (t, url) = match s {
             "official" => ("official", consts::GO_REGISTRY_INDEX),
-            _ => s.split_once("|").unwrap_or(("official", s)),
+            "git" => ("git", consts::GO_SOURCE_GIT_URL),
+            "goproxy" => ("goproxy", consts::GO_PROXY),
+            _ => match (s.split_once('|'), s.split_once("://")) {
+                (Some(legacy), _) => legacy,
+                (None, Some((scheme, _))) => match scheme.split_once('+') {
+                    Some((t, _)) => (t, &s[t.len() + 1..]),
+                    None if scheme == "file" => ("file", s),
+                    None if scheme == "http" || scheme == "https" => ("official", s),
+                    None => return Err(unknown_index_type(scheme, s)),
+                },
+                // scp-like git url, such as git+git@github.com:golang/go
+                (None, None) => match s.split_once('+') {
+                    Some(v) if INDEX_TYPES.iter().any(|t| t.0 == v.0) => v,
+                    _ => {
+                        return Err(anyhow!(
+                            "invalid registry index '{s}', expected `<type>+<url>`, such as `official+https://go.dev`"
+                        ));
+                    }
+                },
+            },
         };
-        let host = host.to_owned();
-        match t {
-            "official" => Ok(Self::Official(host)),
-            "ngx-auto-index" => Ok(Self::NgxAutoIndex(host)),
-            "ngx-fancy-index" => Ok(Self::NgxFancyIndex(host)),
-            "git" => Ok(Self::OfficialGit(host)),
-            _ => Ok(Self::Official(consts::GO_REGISTRY_INDEX.to_owned())),
+        let index = Self::with_type(t, url).ok_or_else(|| unknown_index_type(t, s))?;
+        // git url may be scp-like, such as git@github.com:golang/go
+        if !matches!(index, Self::OfficialGit(_)) {
+            let parsed = url::Url::parse(url)
+                .map_err(|e| anyhow!("invalid url '{url}' of registry index '{s}': {e}"))?;
+            let expected = if matches!(index, Self::File(_)) {
+                &["file"][..]
+            } else {
+                &["http", "https"][..]
+            };
+            if !expected.contains(&parsed.scheme()) {
+                return Err(anyhow!(
+                    "invalid url '{url}' of registry index '{s}', expected {} url",
+                    expected.join("/")
+                ));
+            }
         }
+        Ok(index)
     }
 }
 
 impl Display for RegistryIndexType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Official(host) => write!(f, "official|{host}"),
-            Self::NgxAutoIndex(host) => write!(f, "ngx-auto-index|{host}"),
-            Self::NgxFancyIndex(host) => write!(f, "ngx-fancy-index|{host}"),
-            Self::OfficialGit(url) => write!(f, "git|{url}"),
+            Self::Official(host) => write!(f, "official+{host}"),
+            Self::NgxAutoIndex(host) => write!(f, "nginx-auto+{host}"),
+            Self::NgxFancyIndex(host) => write!(f, "nginx-fancy+{host}"),
+            Self::OfficialGit(url) => write!(f, "git+{url}"),
+            Self::Html(url) => write!(f, "html+{url}"),
+            Self::Json(url) => write!(f, "json+{url}"),
+            Self::S3(spec) => write!(f, "s3+{spec}"),
+            Self::File(url) => write!(f, "{url}"),
+            Self::GoProxy(url) => write!(f, "goproxy+{url}"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{RegistryIndexType, split_registry_indexes};
+
+    #[test]
+    fn test_split_registry_indexes() {
+        assert_eq!(split_registry_indexes("official"), vec!["official"]);
+        assert_eq!(
+            split_registry_indexes(
+                "official,https://golang.google.cn, git|git@github.com:golang/go,goproxy"
+            ),
+            vec![
+                "official",
+                "https://golang.google.cn",
+                " git|git@github.com:golang/go",
+                "goproxy"
+            ]
+        );
+        // the comma of the url is kept.
+        assert_eq!(
+            split_registry_indexes(
+                "html+https://mirrors.example.com/#pattern=go(1.2[0-9]){1,2},file:///srv/go"
+            ),
+            vec![
+                "html+https://mirrors.example.com/#pattern=go(1.2[0-9]){1,2}",
+                "file:///srv/go"
+            ]
+        );
+        assert_eq!(
+            split_registry_indexes("html+https://mirrors.example.com/#selector=td a,th a"),
+            vec!["html+https://mirrors.example.com/#selector=td a,th a"]
+        );
+        // an unknown type is split, and reported when parsing.
+        assert_eq!(
+            split_registry_indexes("official,ngnix-fancy+https://mirrors.example.com/"),
+            vec!["official", "ngnix-fancy+https://mirrors.example.com/"]
+        );
+    }
+
+    #[test]
+    fn test_registry_index_type_from_str() {
+        let parse = |s: &str| s.parse::<RegistryIndexType>();
+        for (s, want) in [
+            (
+                "official",
+                RegistryIndexType::Official("https://go.dev".to_owned()),
+            ),
+            (
+                "https://golang.google.cn",
+                RegistryIndexType::Official("https://golang.google.cn".to_owned()),
+            ),
+            (
+                "official+https://golang.google.cn",
+                RegistryIndexType::Official("https://golang.google.cn".to_owned()),
+            ),
+            (
+                "git+https://go.googlesource.com/go",
+                RegistryIndexType::OfficialGit("https://go.googlesource.com/go".to_owned()),
+            ),
+            (
+                "git|git@github.com:golang/go",
+                RegistryIndexType::OfficialGit("git@github.com:golang/go".to_owned()),
+            ),
+            (
+                "nginx-auto+https://mirrors.example.com/golang/",
+                RegistryIndexType::NgxAutoIndex("https://mirrors.example.com/golang/".to_owned()),
+            ),
+            (
+                "ngx-fancy-index|https://mirrors.example.com/golang/",
+                RegistryIndexType::NgxFancyIndex("https://mirrors.example.com/golang/".to_owned()),
+            ),
+            (
+                "s3+http://127.0.0.1:9000/golang/dl#region=us-east-1",
+                RegistryIndexType::S3(
+                    "http://127.0.0.1:9000/golang/dl#region=us-east-1".to_owned(),
+                ),
+            ),
+            (
+                "file:///srv/go-mirror",
+                RegistryIndexType::File("file:///srv/go-mirror".to_owned()),
+            ),
+        ] {
+            let got = parse(s).unwrap();
+            assert_eq!(got, want, "{s}");
+            // the canonical form round-trips
+            assert_eq!(parse(&got.to_string()).unwrap(), want, "{s}");
+        }
+
+        let err = parse("ngx-autoindex|https://mirrors.example.com/golang/").unwrap_err();
+        assert!(
+            err.to_string().contains("Did you mean `nginx-auto`?"),
+            "{err}"
+        );
+        let err = parse("ngnix-fancy+https://mirrors.example.com/golang/").unwrap_err();
+        assert!(
+            err.to_string().contains("Did you mean `nginx-fancy`?"),
+            "{err}"
+        );
+        assert!(parse("ftp://mirrors.example.com/golang/").is_err());
+        assert!(parse("mirrors.example.com/golang").is_err());
+        assert!(parse("html+not a url").is_err());
+        assert!(parse("file+https://mirrors.example.com/golang/").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use anyhow::anyhow;
+use jiff::{SignedDuration, Timestamp};
+
+use crate::cache::{ArchiveCache, VerifyMethod};
+use crate::registries::go_index::GoIndex;
+use crate::registries::registry_index::multi_index::merge_releases;
+use crate::registries::registry_index::{Fetched, RegistryIndex};
+use crate::registries::release::{GoFile, Release};
+use crate::version::Version;
+use crate::{consts, toolchain};
+
+/// the registry index cached in `index-go.json`.
+///
+/// within the ttl the cached releases are used without any network call,
+/// otherwise fetch with the validators of the cache, `--refresh` forces a fresh fetch.
+/// in offline mode the releases are only from the local index, installed versions and download cache.
+pub struct CachedIndex {
+    inner: Box<dyn RegistryIndex>,
+    source: String,
+    ttl: SignedDuration,
+    refresh: bool,
+}
+
+impl RegistryIndex for CachedIndex {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        if consts::offline() {
+            return offline_releases();
+        }
+        let cached = self.local_index().filter(|_| !self.refresh);
+        if let Some(index) = cached.as_ref().filter(|v| v.is_fresh(self.ttl)) {
+            log::debug!(
+                "Use cached index of {}, fetched at {}",
+                self.source,
+                index.fetched_at.unwrap_or_default()
+            );
+            return Ok(index.releases.clone());
+        }
+        let validators = cached
+            .as_ref()
+            .map(|v| v.validators.clone())
+            .unwrap_or_default();
+        let mut index = match self.inner.list_upstream_releases_if_modified(&validators)? {
+            Fetched::Modified(releases, validators) => GoIndex {
+                validators,
+                ..releases.into()
+            },
+            Fetched::NotModified => {
+                log::debug!("Index of {} is not modified", self.source);
+                cached.ok_or_else(|| anyhow!("not modified without cached index"))?
+            }
+        };
+        index.source = self.source.clone();
+        index.fetched_at = Some(Timestamp::now());
+        if let Err(e) = index.write() {
+            log::warn!("Write cached index failed: {e}");
+        }
+        Ok(index.releases)
+    }
+
+    fn local_index(&self) -> Option<GoIndex> {
+        GoIndex::read(&self.source)
+    }
+}
+
+impl CachedIndex {
+    pub fn new(
+        inner: Box<dyn RegistryIndex>,
+        source: String,
+        ttl: SignedDuration,
+        refresh: bool,
+    ) -> Self {
+        Self {
+            inner,
+            source,
+            ttl,
+            refresh,
+        }
+    }
+}
+
+/// the releases of the local index, the installed versions and the download cache.
+/// the checksum of the cached archive is known only if it was verified.
+fn offline_releases() -> Result<Vec<Release>, anyhow::Error> {
+    let indexes = GoIndex::read_all().into_iter().map(|v| v.releases);
+    let installed = Version::list_go_version()?
+        .into_iter()
+        .filter(|v| toolchain::semantic(&v.version).is_ok())
+        .map(|v| Release::new(v.version))
+        .collect();
+    let cached = Release::from_files(ArchiveCache::open()?.entries()?.into_iter().filter_map(
+        |entry| {
+            let (version, mut file) = GoFile::parse(&entry.meta.filename)?;
+            if entry.meta.verify != VerifyMethod::Unverified {
+                file.sha256 = entry.meta.sha256.clone();
+            }
+            file.size = entry.meta.size;
+            Some((version.to_owned(), file, None))
+        },
+    ));
+    let releases = merge_releases(indexes.chain([installed, cached]));
+    if releases.is_empty() {
+        return Err(anyhow!(
+            "no Go versions known offline, run `goup search` online to fetch the index first"
+        ));
+    }
+    log::debug!("Offline, resolve from {} local versions", releases.len());
+    Ok(releases)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use jiff::SignedDuration;
+
+    use super::{CachedIndex, offline_releases};
+    use crate::cache::{ArchiveCache, VerifyMethod};
+    use crate::consts;
+    use crate::dir::Dir;
+    use crate::registries::go_index::{GoIndex, Validators};
+    use crate::registries::registry_index::{Fetched, RegistryIndex};
+    use crate::registries::release::Release;
+
+    /// answers modified once with an etag, then not modified, records the validators of requests.
+    #[derive(Default)]
+    struct FakeIndex {
+        requests: RefCell<Vec<Validators>>,
+    }
+
+    impl RegistryIndex for Rc<FakeIndex> {
+        fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+            unreachable!()
+        }
+        fn list_upstream_releases_if_modified(
+            &self,
+            validators: &Validators,
+        ) -> Result<Fetched, anyhow::Error> {
+            self.requests.borrow_mut().push(validators.clone());
+            if validators.etag.is_some() {
+                return Ok(Fetched::NotModified);
+            }
+            let validators = Validators {
+                etag: Some("\"v1\"".to_owned()),
+                last_modified: None,
+            };
+            Ok(Fetched::Modified(
+                vec![Release::new("1.21.0"), Release::new("1.22.0")],
+                validators,
+            ))
+        }
+    }
+
+    #[test]
+    fn test_cached_index() {
+        let dir = tempfile::tempdir().unwrap();
+        temp_env::with_var(consts::GOUP_HOME, Some(dir.path()), || {
+            let fake = Rc::new(FakeIndex::default());
+            let index = |ttl: i64, refresh| {
+                CachedIndex::new(
+                    Box::new(Rc::clone(&fake)),
+                    "https://go.dev".to_owned(),
+                    SignedDuration::from_secs(ttl),
+                    refresh,
+                )
+            };
+            let versions = |index: &CachedIndex| index.list_upstream_go_versions().unwrap();
+
+            assert_eq!(versions(&index(3600, false)), vec!["1.21.0", "1.22.0"]);
+            assert_eq!(fake.requests.borrow().len(), 1);
+            // within the ttl
+            assert_eq!(versions(&index(3600, false)), vec!["1.21.0", "1.22.0"]);
+            assert_eq!(fake.requests.borrow().len(), 1);
+            // expired, conditional request
+            assert_eq!(versions(&index(0, false)), vec!["1.21.0", "1.22.0"]);
+            assert_eq!(fake.requests.borrow()[1].etag.as_deref(), Some("\"v1\""));
+            // forced
+            versions(&index(3600, true));
+            assert_eq!(fake.requests.borrow()[2], Validators::default());
+
+            let cached = GoIndex::read("https://go.dev").unwrap();
+            assert_eq!(cached.source, "https://go.dev");
+            assert_eq!(cached.validators.etag.as_deref(), Some("\"v1\""));
+            assert!(cached.fetched_at.is_some());
+            // another source
+            CachedIndex::new(
+                Box::new(Rc::clone(&fake)),
+                "https://golang.google.cn".to_owned(),
+                SignedDuration::from_secs(3600),
+                false,
+            )
+            .list_upstream_releases()
+            .unwrap();
+            assert_eq!(fake.requests.borrow().len(), 4);
+        });
+    }
+
+    #[test]
+    fn test_offline_releases() {
+        let dir = tempfile::tempdir().unwrap();
+        temp_env::with_var(consts::GOUP_HOME, Some(dir.path()), || {
+            assert!(offline_releases().is_err());
+
+            let index: GoIndex = vec![Release::new("1.21.0"), Release::new("1.22.0")].into();
+            index.write().unwrap();
+            Dir::goup_home()
+                .unwrap()
+                .create_dot_unpacked_success_file("go1.20.0")
+                .unwrap();
+            let cache = ArchiveCache::open().unwrap();
+            for (filename, sha256, verify) in [
+                (
+                    "go1.22.0.linux-amd64.tar.gz",
+                    "a".repeat(64),
+                    VerifyMethod::Sha256File,
+                ),
+                (
+                    "go1.23.0.linux-amd64.tar.gz",
+                    "b".repeat(64),
+                    VerifyMethod::Unverified,
+                ),
+            ] {
+                let file = dir.path().join(filename);
+                std::fs::write(&file, "go").unwrap();
+                cache
+                    .insert(&file, &sha256, filename, None, None, verify)
+                    .unwrap();
+            }
+
+            let releases = offline_releases().unwrap();
+            assert_eq!(
+                releases
+                    .iter()
+                    .map(|v| v.version.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["1.20.0", "1.21.0", "1.22.0", "1.23.0"]
+            );
+            let sha256 = |release: &Release| release.files[0].sha256.clone();
+            assert_eq!(sha256(&releases[2]), "a".repeat(64));
+            assert_eq!(sha256(&releases[3]), "");
+        });
+    }
+}
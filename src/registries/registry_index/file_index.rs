@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use jiff::{Timestamp, tz::TimeZone};
+use url::Url;
+
+use crate::registries::registry_index::RegistryIndex;
+use crate::registries::release::{GoFile, Release};
+
+/// a local directory of Go archives, such as a rsync'ed mirror, `file:///srv/go-mirror`.
+///
+/// the versions are built from the archive file names, the sha256 from `{archive}.sha256` if any.
+#[derive(Debug)]
+pub struct FileIndex {
+    url: String,
+}
+
+impl RegistryIndex for FileIndex {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        Self::scan(&file_url_to_path(&self.url)?)
+    }
+}
+
+impl FileIndex {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+        }
+    }
+
+    fn scan(dir: &Path) -> Result<Vec<Release>, anyhow::Error> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir)
+            .map_err(|e| anyhow!("read registry directory {} failed: {e}", dir.display()))?
+        {
+            let entry = entry?;
+            let filename = entry.file_name();
+            let Some((version, mut file)) = filename.to_str().and_then(GoFile::parse) else {
+                continue;
+            };
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            file.size = metadata.len();
+            file.sha256 = fs::read_to_string(dir.join(format!("{}.sha256", file.filename)))
+                .map(|v| v.split_whitespace().next().unwrap_or_default().to_owned())
+                .unwrap_or_default();
+            let date = metadata
+                .modified()
+                .ok()
+                .and_then(|v| Timestamp::try_from(v).ok())
+                .map(|v| v.to_zoned(TimeZone::UTC).date());
+            files.push((version.to_owned(), file, date));
+        }
+        Ok(Release::from_files(files))
+    }
+}
+
+/// `file:///srv/go-mirror` -> `/srv/go-mirror`
+pub(crate) fn file_url_to_path(url: &str) -> Result<PathBuf, anyhow::Error> {
+    Url::parse(url)
+        .ok()
+        .filter(|v| v.scheme() == "file")
+        .and_then(|v| v.to_file_path().ok())
+        .ok_or_else(|| anyhow!("invalid file url '{url}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{FileIndex, file_url_to_path};
+
+    #[test]
+    fn test_scan() -> Result<(), anyhow::Error> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("go1.21.0.linux-amd64.tar.gz"), "a")?;
+        fs::write(
+            dir.path().join("go1.21.0.linux-amd64.tar.gz.sha256"),
+            "ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb\n",
+        )?;
+        fs::write(dir.path().join("go1.21.0.src.tar.gz"), "src")?;
+        fs::write(dir.path().join("go1.22rc1.windows-amd64.zip"), "zip")?;
+        fs::write(dir.path().join("README"), "")?;
+        fs::create_dir(dir.path().join("go1.20.0.linux-amd64.tar.gz"))?;
+
+        let releases = FileIndex::scan(dir.path())?;
+        assert_eq!(
+            releases
+                .iter()
+                .map(|v| v.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1.21.0", "1.22rc1"]
+        );
+        assert_eq!(releases[0].files.len(), 2);
+        assert_eq!(releases[0].has_archive("linux", "amd64"), Some(true));
+        let file = releases[0].file("go1.21.0.linux-amd64.tar.gz").unwrap();
+        assert_eq!(file.size, 1);
+        assert!(file.sha256.starts_with("ca978112"));
+        assert!(!releases[1].stable);
+        assert!(releases[0].date.is_some());
+
+        let url = url::Url::from_directory_path(dir.path()).unwrap();
+        assert_eq!(file_url_to_path(url.as_str())?, dir.path());
+        assert!(file_url_to_path("https://example.com/go").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use crate::registries::goproxy::GoProxy;
+use crate::registries::registry_index::RegistryIndex;
+use crate::registries::release::Release;
+
+/// a GOPROXY, the versions are listed by `golang.org/toolchain/@v/list`.
+///
+/// `goproxy+https://goproxy.example.com`
+#[derive(Debug)]
+pub struct GoProxyIndex {
+    proxy: GoProxy,
+}
+
+impl RegistryIndex for GoProxyIndex {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        let files = self.proxy.list_files()?;
+        Ok(Release::from_files(
+            files
+                .into_iter()
+                .map(|(version, file)| (version, file, None)),
+        ))
+    }
+}
+
+impl GoProxyIndex {
+    pub fn new(url: &str) -> Self {
+        Self {
+            proxy: GoProxy::new(url),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::{HashSet, VecDeque};
+
+use anyhow::anyhow;
+use jiff::civil::Date;
+use regex::Regex;
+use reqwest::blocking::Client;
+use scraper::{ElementRef, Html, Selector};
+use url::Url;
+
+use crate::registries::registry_index::{RegistryIndex, parse_listing_date, parse_listing_size};
+use crate::registries::release::{GoFile, Release};
+
+/// max number of listing pages to fetch, guard against crawling a whole site.
+const MAX_PAGES: usize = 256;
+
+/// a generic html directory listing, such as Apache mod_autoindex, Caddy browse, lighttpd,
+/// nginx autoindex/fancyindex or a static listing page.
+///
+/// options are given in the url fragment:
+/// `html+https://mirrors.example.com/golang/#selector=td.n a&pattern=...&depth=2`
+/// - `selector`: css selector of the file links, default all links `a[href]`.
+/// - `pattern`: regex of the file name, named groups `version`, `os` and `arch`
+///   override the standard Go file name parsing.
+/// - `depth`: depth of following subdirectory and pagination links, default 1.
+#[derive(Debug)]
+pub struct HtmlIndex {
+    url: String,
+    selector: Option<String>,
+    pattern: Option<String>,
+    depth: usize,
+}
+
+/// a page of the listing.
+struct Page {
+    /// file entries of the page, (version, file, date).
+    files: Vec<(String, GoFile, Option<Date>)>,
+    /// subdirectory and pagination links to follow.
+    links: Vec<Url>,
+}
+
+impl RegistryIndex for HtmlIndex {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        let base = Url::parse(&self.url)?;
+        let selector = Selector::parse(self.selector.as_deref().unwrap_or("a[href]"))
+            .map_err(|e| anyhow!("selector {}", e))?;
+        let pattern = self.pattern.as_deref().map(Regex::new).transpose()?;
+
+        let client = Client::new();
+        let mut files = Vec::new();
+        let mut visited = HashSet::from([base.clone()]);
+        let mut queue = VecDeque::from([(base.clone(), 0)]);
+        while let Some((url, depth)) = queue.pop_front() {
+            let text = Self::fetch(&client, &url)?;
+            let page = Self::parse_page(&text, &url, &base, &selector, pattern.as_ref());
+            files.extend(page.files);
+            if depth >= self.depth {
+                continue;
+            }
+            for link in page.links {
+                if visited.len() < MAX_PAGES && visited.insert(link.clone()) {
+                    queue.push_back((link, depth + 1));
+                }
+            }
+        }
+        Ok(Release::from_files(files))
+    }
+}
+
+impl HtmlIndex {
+    /// new from the url with options in the fragment.
+    pub fn new(url: &str) -> Self {
+        let (url, options) = url.split_once('#').unwrap_or((url, ""));
+        let mut index = Self {
+            url: url.to_owned(),
+            selector: None,
+            pattern: None,
+            depth: 1,
+        };
+        for (key, value) in url::form_urlencoded::parse(options.as_bytes()) {
+            match key.as_ref() {
+                "selector" => index.selector = Some(value.into_owned()),
+                "pattern" => index.pattern = Some(value.into_owned()),
+                "depth" => match value.parse() {
+                    Ok(depth) => index.depth = depth,
+                    Err(_) => log::warn!("Invalid html index depth `{value}`, ignored"),
+                },
+                _ => log::warn!("Unknown html index option `{key}`, ignored"),
+            }
+        }
+        index
+    }
+
+    fn fetch(client: &Client, url: &Url) -> Result<String, anyhow::Error> {
+        let resp = client
+            .get(url.as_str())
+            .header("User-Agent", env!("CARGO_PKG_VERSION"))
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("{} unreachable, status {}", url, resp.status()));
+        }
+        Ok(resp.text()?)
+    }
+
+    fn parse_page(
+        text: &str,
+        url: &Url,
+        base: &Url,
+        selector: &Selector,
+        pattern: Option<&Regex>,
+    ) -> Page {
+        let document = Html::parse_document(text);
+        let mut page = Page {
+            files: Vec::new(),
+            links: Vec::new(),
+        };
+        for element in document.select(selector) {
+            let Some(href) = element.value().attr("href") else {
+                continue;
+            };
+            let Ok(link) = url.join(href) else {
+                continue;
+            };
+            let name = link
+                .path_segments()
+                .and_then(|mut v| v.next_back())
+                .unwrap_or_default();
+            if let Some((version, mut file)) = Self::parse_filename(name, pattern) {
+                let (size, date) = Self::detect_size_date(element);
+                file.size = size;
+                page.files.push((version, file, date));
+            } else if Self::is_followable(element, &link, url, base) {
+                page.links.push(link);
+            }
+        }
+        page
+    }
+
+    /// parse the file name by the pattern if any, otherwise the standard Go file name.
+    fn parse_filename(name: &str, pattern: Option<&Regex>) -> Option<(String, GoFile)> {
+        let Some(pattern) = pattern else {
+            return GoFile::parse(name).map(|(version, file)| (version.to_owned(), file));
+        };
+        let captures = pattern.captures(name)?;
+        match captures.name("version") {
+            Some(version) => {
+                let os = captures.name("os").map_or("", |v| v.as_str());
+                let arch = captures.name("arch").map_or("", |v| v.as_str());
+                Some((version.as_str().to_owned(), GoFile::new(name, os, arch)))
+            }
+            None => GoFile::parse(name).map(|(version, file)| (version.to_owned(), file)),
+        }
+    }
+
+    /// follow subdirectories below the base, such as `go1.21.0/`, and pagination links.
+    fn is_followable(element: ElementRef, link: &Url, url: &Url, base: &Url) -> bool {
+        if link.host() != base.host() || !link.path().starts_with(base.path()) || link == url {
+            return false;
+        }
+        if link.path().ends_with('/') && link.path().len() > url.path().len() {
+            return true;
+        }
+        // pagination: <a rel="next">, next page, ›, »
+        let rel_next = element.value().attr("rel").is_some_and(|v| v == "next");
+        let text = element.text().collect::<String>();
+        let text = text.trim().to_lowercase();
+        rel_next || text.starts_with("next") || text == "›" || text == "»"
+    }
+
+    /// detect size and date of the link, from the table row or the text after the link.
+    fn detect_size_date(element: ElementRef) -> (u64, Option<Date>) {
+        let row = element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .find(|v| v.value().name() == "tr");
+        let texts: Vec<String> = match row {
+            // Apache, lighttpd, Caddy and fancyindex tables, skip the cell of the link.
+            Some(row) => row
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|cell| !cell.descendants().any(|v| v.id() == element.id()))
+                .flat_map(|cell| {
+                    // Caddy: <td data-order="66616678">, <time datetime="2023-08-08T20:10:00Z">
+                    let attrs = cell
+                        .descendants()
+                        .filter_map(ElementRef::wrap)
+                        .filter_map(|v| {
+                            v.value()
+                                .attr("data-order")
+                                .or_else(|| v.value().attr("datetime"))
+                        })
+                        .map(ToOwned::to_owned)
+                        .collect::<Vec<_>>();
+                    attrs
+                        .into_iter()
+                        .chain([cell.text().collect::<String>().trim().to_owned()])
+                })
+                .collect(),
+            // nginx autoindex and Apache <pre>: <a>...</a>   07-May-2024 21:12   68765432
+            None => element
+                .next_sibling()
+                .and_then(|v| v.value().as_text())
+                .map(|v| {
+                    let v = v.trim();
+                    match v.rsplit_once(char::is_whitespace) {
+                        Some((date, size)) => vec![date.trim().to_owned(), size.to_owned()],
+                        None => vec![v.to_owned()],
+                    }
+                })
+                .unwrap_or_default(),
+        };
+        let date = texts.iter().find_map(|v| parse_listing_date(v));
+        let size = texts
+            .iter()
+            .filter(|v| parse_listing_date(v).is_none())
+            .map(|v| parse_listing_size(v))
+            .find(|&v| v > 0)
+            .unwrap_or_default();
+        (size, date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::civil::date;
+    use regex::Regex;
+    use scraper::Selector;
+    use url::Url;
+
+    use super::HtmlIndex;
+
+    fn parse(text: &str, pattern: Option<&str>) -> super::Page {
+        let url = Url::parse("https://mirrors.example.com/golang/").unwrap();
+        let selector = Selector::parse("a[href]").unwrap();
+        let pattern = pattern.map(|v| Regex::new(v).unwrap());
+        HtmlIndex::parse_page(text, &url, &url, &selector, pattern.as_ref())
+    }
+
+    #[test]
+    fn test_new() {
+        let index = HtmlIndex::new("https://example.com/go/#selector=td.n%20a&depth=2");
+        assert_eq!(index.url, "https://example.com/go/");
+        assert_eq!(index.selector.as_deref(), Some("td.n a"));
+        assert_eq!(index.pattern, None);
+        assert_eq!(index.depth, 2);
+    }
+
+    #[test]
+    fn test_parse_page() {
+        // Apache mod_autoindex
+        let page = parse(
+            r#"<table><tr><th>Name</th><th>Last modified</th><th>Size</th></tr>
+            <tr><td><a href="../">Parent Directory</a></td><td>&nbsp;</td><td>-</td></tr>
+            <tr><td><a href="go1.21.0.linux-amd64.tar.gz">go1.21.0.linux-amd64.tar.gz</a></td><td align="right">2023-08-08 20:10  </td><td align="right"> 63M</td></tr>
+            <tr><td><a href="go1.21.0.linux-amd64.tar.gz.sha256">go1.21.0.linux-amd64.tar.gz.sha256</a></td><td align="right">2023-08-08 20:10  </td><td align="right"> 64</td></tr>
+            <tr><td><a href="archive/">archive/</a></td><td align="right">2023-08-08 20:10  </td><td align="right"> - </td></tr>
+            </table>"#,
+            None,
+        );
+        assert_eq!(page.files.len(), 1);
+        let (version, file, got_date) = &page.files[0];
+        assert_eq!(version, "1.21.0");
+        assert_eq!((file.os.as_str(), file.arch.as_str()), ("linux", "amd64"));
+        assert_eq!(file.size, 63 << 20);
+        assert_eq!(*got_date, Some(date(2023, 8, 8)));
+        assert_eq!(
+            page.links.iter().map(Url::as_str).collect::<Vec<_>>(),
+            vec!["https://mirrors.example.com/golang/archive/"]
+        );
+
+        // Caddy browse
+        let page = parse(
+            r#"<table><tbody><tr class="file"><td></td>
+            <td><a href="./go1.21.0.darwin-arm64.tar.gz"><span class="name">go1.21.0.darwin-arm64.tar.gz</span></a></td>
+            <td data-order="64876544" class="size"><div class="sizebar"><div class="sizebar-text">62 MiB</div></div></td>
+            <td class="timestamp"><time datetime="2023-08-08T20:10:00Z">08/08/2023 08:10:00 PM +00:00</time></td>
+            </tr></tbody></table>
+            <a href="/golang/?page=2" rel="next">Next</a>"#,
+            None,
+        );
+        assert_eq!(page.files.len(), 1);
+        assert_eq!(page.files[0].1.size, 64876544);
+        assert_eq!(page.files[0].2, Some(date(2023, 8, 8)));
+        assert_eq!(
+            page.links.iter().map(Url::as_str).collect::<Vec<_>>(),
+            vec!["https://mirrors.example.com/golang/?page=2"]
+        );
+
+        // nginx autoindex, custom file name pattern
+        let page = parse(
+            r#"<pre><a href="../">../</a>
+<a href="golang-1.22.0-linux-x64.tgz">golang-1.22.0-linux-x64.tgz</a>     07-Feb-2024 21:12    68765432
+<a href="go1.22.0.src.tar.gz">go1.22.0.src.tar.gz</a>     07-Feb-2024 21:12    26964496
+</pre>"#,
+            Some(r"^golang-(?<version>[^-]+)-(?<os>[a-z]+)-(?<arch>[a-z0-9]+)\.tgz$"),
+        );
+        assert_eq!(page.files.len(), 1);
+        let (version, file, got_date) = &page.files[0];
+        assert_eq!(version, "1.22.0");
+        assert_eq!((file.os.as_str(), file.arch.as_str()), ("linux", "x64"));
+        assert_eq!(file.size, 68765432);
+        assert_eq!(*got_date, Some(date(2024, 2, 7)));
+    }
+}
@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use reqwest::blocking::Client;
+use serde_json::Value;
+
+use crate::registries::registry_index::{RegistryIndex, parse_listing_date, parse_listing_size};
+use crate::registries::release::{GoFile, Release};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// a JSON manifest of Go releases, the fields are located by JSON pointers.
+///
+/// the mapping is given in the url fragment, the defaults follow the go.dev `dl/?mode=json` shape:
+/// `json+https://mirrors.example.com/go/releases.json#releases=/data&version=/tag&files=/assets`
+/// - `releases`: pointer of the release array in the document, default the root.
+/// - `version`, `stable`, `date`, `files`: pointers in a release, default `/version`, `/stable`,
+///   `/date` and `/files`. `stable` is inferred from the version if missing.
+/// - `filename`, `os`, `arch`, `sha256`, `size`: pointers in a file, default the same name.
+///   `filename` may be an url, `os` and `arch` are parsed from the file name if missing.
+#[derive(Debug)]
+pub struct JsonIndex {
+    url: String,
+    mapping: Mapping,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mapping {
+    releases: String,
+    version: String,
+    stable: String,
+    date: String,
+    files: String,
+    filename: String,
+    os: String,
+    arch: String,
+    sha256: String,
+    size: String,
+}
+
+impl Default for Mapping {
+    fn default() -> Self {
+        Self {
+            releases: String::new(),
+            version: "/version".to_owned(),
+            stable: "/stable".to_owned(),
+            date: "/date".to_owned(),
+            files: "/files".to_owned(),
+            filename: "/filename".to_owned(),
+            os: "/os".to_owned(),
+            arch: "/arch".to_owned(),
+            sha256: "/sha256".to_owned(),
+            size: "/size".to_owned(),
+        }
+    }
+}
+
+impl RegistryIndex for JsonIndex {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        let resp = Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()?
+            .get(&self.url)
+            .header("User-Agent", env!("CARGO_PKG_VERSION"))
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(
+                "{} unreachable, status {}",
+                self.url,
+                resp.status()
+            ));
+        }
+        self.parse(&resp.json()?)
+    }
+}
+
+impl JsonIndex {
+    /// new from the url with the mapping in the fragment.
+    pub fn new(url: &str) -> Self {
+        let (url, options) = url.split_once('#').unwrap_or((url, ""));
+        let mut mapping = Mapping::default();
+        for (key, value) in url::form_urlencoded::parse(options.as_bytes()) {
+            let field = match key.as_ref() {
+                "releases" => &mut mapping.releases,
+                "version" => &mut mapping.version,
+                "stable" => &mut mapping.stable,
+                "date" => &mut mapping.date,
+                "files" => &mut mapping.files,
+                "filename" => &mut mapping.filename,
+                "os" => &mut mapping.os,
+                "arch" => &mut mapping.arch,
+                "sha256" => &mut mapping.sha256,
+                "size" => &mut mapping.size,
+                _ => {
+                    log::warn!("Unknown json index option `{key}`, ignored");
+                    continue;
+                }
+            };
+            *field = value.into_owned();
+        }
+        Self {
+            url: url.to_owned(),
+            mapping,
+        }
+    }
+
+    fn parse(&self, document: &Value) -> Result<Vec<Release>, anyhow::Error> {
+        let m = &self.mapping;
+        let items = document
+            .pointer(&m.releases)
+            .and_then(Value::as_array)
+            .ok_or_else(|| anyhow!("no release array at `{}` of {}", m.releases, self.url))?;
+        let mut releases = items
+            .iter()
+            .filter_map(|item| {
+                let version = item.pointer(&m.version).and_then(Value::as_str)?;
+                let version = version.trim_start_matches("go").trim_start_matches('v');
+                let mut release = Release::new(version);
+                if let Some(stable) = item.pointer(&m.stable).and_then(as_bool) {
+                    release.stable = stable;
+                }
+                release.date = item
+                    .pointer(&m.date)
+                    .and_then(Value::as_str)
+                    .and_then(parse_listing_date);
+                release.files = item
+                    .pointer(&m.files)
+                    .and_then(Value::as_array)
+                    .map(|files| files.iter().filter_map(|v| self.parse_file(v)).collect())
+                    .unwrap_or_default();
+                Some(release)
+            })
+            .collect();
+        Release::sort(&mut releases);
+        Ok(releases)
+    }
+
+    fn parse_file(&self, value: &Value) -> Option<GoFile> {
+        let m = &self.mapping;
+        let str_of = |pointer: &str| value.pointer(pointer).and_then(Value::as_str);
+        // https://mirrors.example.com/go/go1.22.0.linux-amd64.tar.gz -> go1.22.0.linux-amd64.tar.gz
+        let filename = str_of(&m.filename)?.rsplit('/').next()?;
+        let mut file = match (str_of(&m.os), str_of(&m.arch)) {
+            (Some(os), Some(arch)) => GoFile::new(filename, os, arch),
+            _ => GoFile::parse(filename)?.1,
+        };
+        file.sha256 = str_of(&m.sha256).unwrap_or_default().to_lowercase();
+        file.size = match value.pointer(&m.size) {
+            Some(Value::Number(v)) => v.as_u64().unwrap_or_default(),
+            Some(Value::String(v)) => parse_listing_size(v),
+            _ => 0,
+        };
+        Some(file)
+    }
+}
+
+/// `true`, `"true"` or `"stable"` as stable.
+fn as_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(v) => Some(*v),
+        Value::String(v) => {
+            Some(v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("stable"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::civil::date;
+    use serde_json::json;
+
+    use super::JsonIndex;
+    use crate::registries::release::FileKind;
+
+    #[test]
+    fn test_parse() -> Result<(), anyhow::Error> {
+        let index = JsonIndex::new(
+            "https://mirrors.example.com/go/releases.json#releases=/data/items&version=/tag&stable=/channel&date=/published&files=/assets&filename=/url&sha256=/checksum",
+        );
+        assert_eq!(index.url, "https://mirrors.example.com/go/releases.json");
+        let document = json!({
+            "data": {
+                "items": [
+                    {
+                        "tag": "v1.22.0",
+                        "channel": "stable",
+                        "published": "2024-02-06",
+                        "assets": [
+                            {
+                                "url": "https://mirrors.example.com/go/go1.22.0.linux-amd64.tar.gz",
+                                "checksum": "F6C8A87AA03B92C4B0BF3D558E28EA03006EB29DB78917DAEC5CFB6EC1046265",
+                                "size": 68988925
+                            },
+                            { "url": "https://mirrors.example.com/go/go1.22.0.src.tar.gz", "size": "26M" }
+                        ]
+                    },
+                    { "tag": "go1.22rc2" },
+                    { "name": "no version" }
+                ]
+            }
+        });
+        let releases = index.parse(&document)?;
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].version, "1.22rc2");
+        assert!(!releases[0].stable);
+        assert!(releases[0].files.is_empty());
+
+        let release = &releases[1];
+        assert_eq!(release.version, "1.22.0");
+        assert!(release.stable);
+        assert_eq!(release.date, Some(date(2024, 2, 6)));
+        assert_eq!(release.has_archive("linux", "amd64"), Some(true));
+        let file = release.file("go1.22.0.linux-amd64.tar.gz").unwrap();
+        assert_eq!(
+            file.sha256,
+            "f6c8a87aa03b92c4b0bf3d558e28ea03006eb29db78917daec5cfb6ec1046265"
+        );
+        assert_eq!(file.size, 68988925);
+        let file = release.file("go1.22.0.src.tar.gz").unwrap();
+        assert_eq!((file.kind, file.size), (FileKind::Source, 26 << 20));
+
+        assert!(index.parse(&json!([])).is_err());
+        Ok(())
+    }
+}
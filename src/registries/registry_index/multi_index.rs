@@ -0,0 +1,249 @@
+use std::mem;
+use std::sync::{Mutex, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+
+use crate::registries::go_index::GoIndex;
+use crate::registries::registry_index::{RegistryIndex, RegistryIndexType};
+use crate::registries::release::Release;
+use crate::toolchain;
+
+/// how long the other indexes are waited for after the first successful answer,
+/// to report the unreachable and stale ones.
+const GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// several registry indexes queried concurrently, use the first successful answer,
+/// or merge and dedupe the answers of all indexes.
+#[derive(Debug)]
+pub struct MultiIndex {
+    indexes: Vec<RegistryIndexType>,
+    merge: bool,
+}
+
+/// the queries left behind after the grace period, joined before exit.
+static STRAGGLERS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// wait for the queries left behind, so that they are not interrupted on exit.
+pub fn join_stragglers() {
+    let stragglers = STRAGGLERS
+        .lock()
+        .map(|mut v| mem::take(&mut *v))
+        .unwrap_or_default();
+    for handle in stragglers {
+        handle.join().ok();
+    }
+}
+
+impl RegistryIndex for MultiIndex {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        let (tx, rx) = mpsc::channel();
+        let handles = self
+            .indexes
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, index)| {
+                let tx = tx.clone();
+                // the slow indexes are left behind after the grace period in first successful mode.
+                thread::spawn(move || {
+                    let releases = index.as_registry_index().list_upstream_releases();
+                    tx.send((i, releases)).ok();
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(tx);
+        if let Ok(mut stragglers) = STRAGGLERS.lock() {
+            stragglers.extend(handles);
+        }
+
+        let mut answered = vec![false; self.indexes.len()];
+        let mut answers = Vec::new();
+        let mut failures = Vec::new();
+        let mut deadline: Option<Instant> = None;
+        loop {
+            // 首个成功后, 在宽限期内继续接收其余索引的结果, 以便报告失败和过时的索引
+            let received = match deadline {
+                None => rx.recv().ok(),
+                Some(deadline) => rx
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok(),
+            };
+            let Some((i, result)) = received else {
+                break;
+            };
+            answered[i] = true;
+            let index = &self.indexes[i];
+            match result {
+                Ok(releases) if releases.is_empty() => {
+                    log::warn!("Registry index {index} returns no versions");
+                    failures.push(format!("{index}: no versions"));
+                }
+                Ok(releases) => {
+                    if !self.merge && deadline.is_none() {
+                        log::debug!("Use registry index {index}");
+                        deadline = Some(Instant::now() + GRACE_PERIOD);
+                    }
+                    answers.push((i, releases));
+                }
+                Err(e) => {
+                    log::warn!("Registry index {index} is unreachable: {e}");
+                    failures.push(format!("{index}: {e}"));
+                }
+            }
+        }
+        if answers.is_empty() {
+            return Err(anyhow!(
+                "all registry indexes failed:\n  {}",
+                failures.join("\n  ")
+            ));
+        }
+        // 未在宽限期内应答的索引, 使用其缓存的副本作为过时检查的参照
+        let cached = answered
+            .iter()
+            .enumerate()
+            .filter(|(_, answered)| !**answered)
+            .filter_map(|(i, _)| {
+                let index = &self.indexes[i];
+                log::warn!("Registry index {index} did not answer within {GRACE_PERIOD:?}");
+                GoIndex::read(&index.to_string()).map(|v| v.releases)
+            })
+            .collect::<Vec<_>>();
+        self.report_stale(&answers, &cached);
+        if self.merge {
+            Ok(merge_releases(answers.into_iter().map(|v| v.1)))
+        } else {
+            // the first successful answer.
+            Ok(answers.swap_remove(0).1)
+        }
+    }
+}
+
+impl MultiIndex {
+    pub fn new(indexes: Vec<RegistryIndexType>, merge: bool) -> Self {
+        Self { indexes, merge }
+    }
+
+    /// warn the indexes whose latest stable version is behind the others,
+    /// or behind the cached copies of the indexes not answered.
+    fn report_stale(&self, answers: &[(usize, Vec<Release>)], cached: &[Vec<Release>]) {
+        let Some((newest, newest_release)) = answers
+            .iter()
+            .map(|(_, releases)| releases)
+            .chain(cached)
+            .filter_map(|releases| latest(releases))
+            .max_by(|a, b| a.0.cmp(&b.0))
+        else {
+            return;
+        };
+        for (i, releases) in answers {
+            match latest(releases) {
+                Some((semver, release)) if semver < newest => log::warn!(
+                    "Registry index {} is stale, latest {} is behind {}",
+                    self.indexes[*i],
+                    release.version,
+                    newest_release.version
+                ),
+                None => log::warn!(
+                    "Registry index {} is stale, no stable version",
+                    self.indexes[*i]
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// the latest stable release of the sorted releases.
+fn latest(releases: &[Release]) -> Option<(semver::Version, &Release)> {
+    releases
+        .iter()
+        .rev()
+        .find(|v| v.stable)
+        .and_then(|v| toolchain::semantic(&v.version).ok().map(|s| (s, v)))
+}
+
+/// merge and dedupe the releases by version, the files by file name,
+/// the checksum, size and date are filled in from any answer.
+pub(crate) fn merge_releases<I>(answers: I) -> Vec<Release>
+where
+    I: IntoIterator<Item = Vec<Release>>,
+{
+    let mut merged: Vec<Release> = Vec::new();
+    for release in answers.into_iter().flatten() {
+        let Some(existing) = merged.iter_mut().find(|v| v.version == release.version) else {
+            merged.push(release);
+            continue;
+        };
+        existing.date = match (existing.date, release.date) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        for file in release.files {
+            match existing
+                .files
+                .iter_mut()
+                .find(|v| v.filename == file.filename)
+            {
+                Some(v) => {
+                    if v.sha256.is_empty() {
+                        v.sha256 = file.sha256;
+                    }
+                    if v.size == 0 {
+                        v.size = file.size;
+                    }
+                }
+                None => existing.files.push(file),
+            }
+        }
+    }
+    Release::sort(&mut merged);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::civil::date;
+
+    use super::merge_releases;
+    use crate::registries::release::{GoFile, Release};
+
+    #[test]
+    fn test_merge_releases() {
+        let release = |version: &str, files: Vec<GoFile>| Release {
+            files,
+            ..Release::new(version)
+        };
+        let mut with_sha256 = GoFile::new("go1.21.0.linux-amd64.tar.gz", "linux", "amd64");
+        with_sha256.sha256 = "abc".to_owned();
+        let mut with_size = GoFile::new("go1.21.0.linux-amd64.tar.gz", "linux", "amd64");
+        with_size.size = 42;
+        let mut dated = release("1.21.0", vec![with_size]);
+        dated.date = Some(date(2023, 8, 8));
+
+        let merged = merge_releases([
+            vec![
+                release("1.21.0", vec![with_sha256]),
+                release("1.22.0", vec![]),
+            ],
+            vec![
+                release("1.20.0", vec![]),
+                dated,
+                release("1.21.0", vec![GoFile::new("go1.21.0.src.tar.gz", "", "")]),
+            ],
+        ]);
+        assert_eq!(
+            merged
+                .iter()
+                .map(|v| v.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1.20.0", "1.21.0", "1.22.0"]
+        );
+        let release = &merged[1];
+        assert_eq!(release.date, Some(date(2023, 8, 8)));
+        assert_eq!(release.files.len(), 2);
+        let file = release.file("go1.21.0.linux-amd64.tar.gz").unwrap();
+        assert_eq!((file.sha256.as_str(), file.size), ("abc", 42));
+    }
+}
@@ -2,7 +2,8 @@ use anyhow::anyhow;
 use reqwest::blocking::Client;
 use scraper::{Html, Selector};
 
-use crate::registries::{go_index::GoIndex, registry_index::RegistryIndex};
+use crate::registries::registry_index::{RegistryIndex, parse_listing_date, parse_listing_size};
+use crate::registries::release::{GoFile, Release};
 
 #[derive(Debug)]
 pub struct NgxAutoIndex {
@@ -10,21 +11,7 @@ pub struct NgxAutoIndex {
 }
 
 impl RegistryIndex for NgxAutoIndex {
-    fn get_upstream_latest_go_version(&self) -> Result<String, anyhow::Error> {
-        self.inner_list_upstream_go_versions().map(|i| i.latest)
-    }
-    fn list_upstream_go_versions(&self) -> Result<Vec<String>, anyhow::Error> {
-        self.inner_list_upstream_go_versions().map(|i| i.versions)
-    }
-}
-
-impl NgxAutoIndex {
-    pub fn new(host: &str) -> NgxAutoIndex {
-        Self {
-            host: host.to_owned(),
-        }
-    }
-    fn inner_list_upstream_go_versions(&self) -> Result<GoIndex, anyhow::Error> {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
         let resp = Client::new()
             .get(&self.host)
             .header("User-Agent", env!("CARGO_PKG_VERSION"))
@@ -39,23 +26,29 @@ impl NgxAutoIndex {
         let text = resp.text()?;
         let document = Html::parse_document(&text);
         let selector = Selector::parse("pre a").map_err(|e| anyhow!("selector {}", e))?;
-        let items: Vec<String> = document
-            .select(&selector)
-            .filter_map(|element| {
-                let href = element.value().attr("href")?;
-                let href = href.trim_start_matches("/golang/");
-                if !href.starts_with("go") || href.ends_with('/') || !href.ends_with(".src.tar.gz")
-                {
-                    return None;
-                }
-                // go1.22.3.src.tar.gz -> go1.22.3
-                let ver = href
-                    .trim_start_matches("/golang/")
-                    .trim_start_matches("go")
-                    .trim_end_matches(".src.tar.gz");
-                Some(ver.to_owned())
-            })
-            .collect();
-        Ok(items.into())
+        let files = document.select(&selector).filter_map(|element| {
+            let href = element.value().attr("href")?;
+            // /golang/go1.22.3.linux-amd64.tar.gz -> go1.22.3.linux-amd64.tar.gz
+            let filename = href.rsplit('/').next()?;
+            let (version, mut file) = GoFile::parse(filename)?;
+            // <a href="...">...</a>   07-May-2024 21:12   68765432
+            let (date, size) = element
+                .next_sibling()
+                .and_then(|v| v.value().as_text())
+                .and_then(|v| v.trim().rsplit_once(char::is_whitespace))
+                .map(|(date, size)| (parse_listing_date(date.trim()), parse_listing_size(size)))
+                .unwrap_or_default();
+            file.size = size;
+            Some((version.to_owned(), file, date))
+        });
+        Ok(Release::from_files(files))
+    }
+}
+
+impl NgxAutoIndex {
+    pub fn new(host: &str) -> NgxAutoIndex {
+        Self {
+            host: host.to_owned(),
+        }
     }
 }
@@ -2,7 +2,8 @@ use anyhow::anyhow;
 use reqwest::blocking::Client;
 use scraper::{Html, Selector};
 
-use crate::registries::{go_index::GoIndex, registry_index::RegistryIndex};
+use crate::registries::registry_index::{RegistryIndex, parse_listing_date, parse_listing_size};
+use crate::registries::release::{GoFile, Release};
 
 #[derive(Debug)]
 pub struct NgxFancyIndex {
@@ -10,22 +11,7 @@ pub struct NgxFancyIndex {
 }
 
 impl RegistryIndex for NgxFancyIndex {
-    fn get_upstream_latest_go_version(&self) -> Result<String, anyhow::Error> {
-        self.inner_list_upstream_go_versions().map(|i| i.latest)
-    }
-    fn list_upstream_go_versions(&self) -> Result<Vec<String>, anyhow::Error> {
-        self.inner_list_upstream_go_versions().map(|i| i.versions)
-    }
-}
-
-impl NgxFancyIndex {
-    pub fn new(host: &str) -> NgxFancyIndex {
-        Self {
-            host: host.to_owned(),
-        }
-    }
-
-    fn inner_list_upstream_go_versions(&self) -> Result<GoIndex, anyhow::Error> {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
         let resp = Client::new()
             .get(&self.host)
             .header("User-Agent", env!("CARGO_PKG_VERSION"))
@@ -39,27 +25,28 @@ impl NgxFancyIndex {
         }
         let text = resp.text()?;
         let document = Html::parse_document(&text);
-        let selector =
-            Selector::parse("table tbody tr td a").map_err(|e| anyhow!("selector {}", e))?;
-        let items: Vec<String> = document
-            .select(&selector)
-            .filter_map(|element| {
-                let filename = element.text().collect::<String>();
-                let filename = filename.trim();
-                if !filename.starts_with("go")
-                    || filename.ends_with('/')
-                    || !filename.ends_with(".src.tar.gz")
-                {
-                    None
-                } else {
-                    // go1.22.3.src.tar.gz -> go1.22.3
-                    let ver = filename
-                        .trim_start_matches("go")
-                        .trim_end_matches(".src.tar.gz");
-                    Some(ver.to_owned())
-                }
-            })
-            .collect();
-        Ok(items.into())
+        let row_selector =
+            Selector::parse("table tbody tr").map_err(|e| anyhow!("selector {}", e))?;
+        let cell_selector = Selector::parse("td").map_err(|e| anyhow!("selector {}", e))?;
+        let files = document.select(&row_selector).filter_map(|row| {
+            // <td class="link"><a>go1.22.3.linux-amd64.tar.gz</a></td><td class="size">65.6 MiB</td><td class="date">2024-May-07 21:12</td>
+            let cells: Vec<String> = row
+                .select(&cell_selector)
+                .map(|v| v.text().collect::<String>().trim().to_owned())
+                .collect();
+            let (version, mut file) = GoFile::parse(cells.first()?)?;
+            file.size = cells.get(1).map_or(0, |v| parse_listing_size(v));
+            let date = cells.get(2).and_then(|v| parse_listing_date(v));
+            Some((version.to_owned(), file, date))
+        });
+        Ok(Release::from_files(files))
+    }
+}
+
+impl NgxFancyIndex {
+    pub fn new(host: &str) -> NgxFancyIndex {
+        Self {
+            host: host.to_owned(),
+        }
     }
 }
@@ -1,31 +1,24 @@
 use std::time::Duration;
 
 use anyhow::anyhow;
+use reqwest::StatusCode;
 use reqwest::blocking::Client;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::registries::registry_index::RegistryIndex;
+use crate::registries::go_index::Validators;
+use crate::registries::registry_index::{Fetched, RegistryIndex};
+use crate::registries::release::{GoFile, Release};
 
 const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
 
-#[allow(dead_code)]
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GoFile {
-    pub arch: String,
-    pub filename: String,
-    pub kind: String,
-    pub os: String,
-    pub sha256: String,
-    pub size: isize,
-    pub version: String,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 struct GoRelease {
     pub version: String,
     pub stable: bool,
-    // pub files: Vec<GoFile>,
+    #[serde(default)]
+    pub files: Vec<GoFile>,
 }
 
 #[derive(Debug, Clone)]
@@ -48,18 +41,56 @@ impl RegistryIndex for Official {
             .map(|v| v.to_owned())
     }
 
-    /// list upstream go versions.
-    fn list_upstream_go_versions(&self) -> Result<Vec<String>, anyhow::Error> {
-        Ok(Client::builder()
+    /// list upstream go releases.
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        match self.list_upstream_releases_if_modified(&Validators::default())? {
+            Fetched::Modified(releases, _) => Ok(releases),
+            Fetched::NotModified => Err(anyhow!("Unexpected not modified of Go releases")),
+        }
+    }
+
+    /// list upstream go releases, with `If-None-Match` and `If-Modified-Since`.
+    fn list_upstream_releases_if_modified(
+        &self,
+        validators: &Validators,
+    ) -> Result<Fetched, anyhow::Error> {
+        let mut req = Client::builder()
             .timeout(HTTP_TIMEOUT)
             .build()?
-            .get(format!("{}/dl/?mode=json&include=all", self.host))
-            .send()?
+            .get(format!("{}/dl/?mode=json&include=all", self.host));
+        if let Some(etag) = &validators.etag {
+            req = req.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            req = req.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        let resp = req.send()?;
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            return Ok(Fetched::NotModified);
+        }
+        let resp = resp.error_for_status()?;
+        let header = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        let validators = Validators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+        let mut releases = resp
             .json::<Vec<GoRelease>>()?
             .into_iter()
-            .map(|v| v.version.trim_start_matches("go").to_string())
-            .rev()
-            .collect())
+            .map(|v| Release {
+                version: v.version.trim_start_matches("go").to_string(),
+                stable: v.stable,
+                date: None,
+                files: v.files,
+            })
+            .collect();
+        Release::sort(&mut releases);
+        Ok(Fetched::Modified(releases, validators))
     }
 }
 
@@ -4,7 +4,7 @@ use anyhow::anyhow;
 use regex::Regex;
 use which::which;
 
-use crate::registries::{go_index::GoIndex, registry_index::RegistryIndex};
+use crate::registries::{go_index::GoIndex, registry_index::RegistryIndex, release::Release};
 
 #[derive(Debug)]
 pub struct OfficialGit {
@@ -12,11 +12,8 @@ pub struct OfficialGit {
 }
 
 impl RegistryIndex for OfficialGit {
-    fn get_upstream_latest_go_version(&self) -> Result<String, anyhow::Error> {
-        self.inner_list_upstream_go_versions().map(|i| i.latest)
-    }
-    fn list_upstream_go_versions(&self) -> Result<Vec<String>, anyhow::Error> {
-        self.inner_list_upstream_go_versions().map(|i| i.versions)
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        self.inner_list_upstream_go_versions().map(|i| i.releases)
     }
 }
 
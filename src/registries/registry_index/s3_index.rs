@@ -0,0 +1,34 @@
+use crate::registries::registry_index::{RegistryIndex, parse_listing_date};
+use crate::registries::release::{GoFile, Release};
+use crate::registries::s3::S3Bucket;
+
+/// an S3-compatible bucket, the versions are built from the objects under the prefix.
+///
+/// `s3+http://127.0.0.1:9000/golang/dl#region=us-east-1`
+#[derive(Debug)]
+pub struct S3Index {
+    spec: String,
+}
+
+impl RegistryIndex for S3Index {
+    fn list_upstream_releases(&self) -> Result<Vec<Release>, anyhow::Error> {
+        let objects = S3Bucket::parse(&self.spec)?.list_objects()?;
+        let files = objects.into_iter().filter_map(|object| {
+            // dl/go1.22.3.linux-amd64.tar.gz -> go1.22.3.linux-amd64.tar.gz
+            let filename = object.key.rsplit('/').next()?;
+            let (version, mut file) = GoFile::parse(filename)?;
+            file.size = object.size;
+            let date = object.last_modified.as_deref().and_then(parse_listing_date);
+            Some((version.to_owned(), file, date))
+        });
+        Ok(Release::from_files(files))
+    }
+}
+
+impl S3Index {
+    pub fn new(spec: &str) -> Self {
+        Self {
+            spec: spec.to_owned(),
+        }
+    }
+}
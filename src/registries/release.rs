@@ -0,0 +1,249 @@
+use std::cmp::Ordering;
+
+use jiff::civil::Date;
+use serde::{Deserialize, Serialize};
+
+use crate::registries::registry::parse_archive_filename;
+use crate::toolchain;
+
+/// kind of a downloadable file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    /// binary archive, such as go1.21.5.linux-amd64.tar.gz
+    Archive,
+    /// installer, such as go1.21.5.darwin-arm64.pkg
+    Installer,
+    /// source archive, go1.21.5.src.tar.gz
+    Source,
+    /// missing, or a kind unknown to goup.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// a downloadable file of a Go release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoFile {
+    /// file name, such as go1.21.5.linux-amd64.tar.gz
+    pub filename: String,
+    /// os, empty for the source archive.
+    #[serde(default)]
+    pub os: String,
+    /// arch, empty for the source archive.
+    #[serde(default)]
+    pub arch: String,
+    /// kind of file, `archive`, `installer` or `source`.
+    #[serde(default)]
+    pub kind: FileKind,
+    /// sha256 of the file, empty if unknown.
+    #[serde(default)]
+    pub sha256: String,
+    /// size of the file, 0 if unknown.
+    #[serde(default)]
+    pub size: u64,
+}
+
+impl GoFile {
+    /// parse the file name, returns the version and the file.
+    /// go1.21.5.linux-amd64.tar.gz -> (1.21.5, archive of linux/amd64)
+    /// go1.21.5.darwin-arm64.pkg   -> (1.21.5, installer of darwin/arm64)
+    /// go1.21.5.src.tar.gz         -> (1.21.5, source)
+    pub fn parse(filename: &str) -> Option<(&str, GoFile)> {
+        if let Some((version, os, arch)) = parse_archive_filename(filename) {
+            return Some((version, GoFile::new(filename, os, arch)));
+        }
+        let version = filename
+            .strip_prefix("go")?
+            .strip_suffix(".src.tar.gz")
+            .filter(|v| !v.is_empty())?;
+        Some((version, GoFile::new(filename, "", "")))
+    }
+
+    /// a file with unknown sha256 and size, the kind is inferred from the extension.
+    pub fn new(filename: &str, os: &str, arch: &str) -> Self {
+        let kind = if os.is_empty() {
+            FileKind::Source
+        } else if filename.ends_with(".pkg") || filename.ends_with(".msi") {
+            FileKind::Installer
+        } else {
+            FileKind::Archive
+        };
+        GoFile {
+            filename: filename.to_owned(),
+            os: os.to_owned(),
+            arch: arch.to_owned(),
+            kind,
+            sha256: String::new(),
+            size: 0,
+        }
+    }
+}
+
+/// a Go release, backends fill in as far as they can.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    /// version without `go` prefix, such as 1.21.5, 1.22rc1
+    pub version: String,
+    /// whether it is a stable release.
+    pub stable: bool,
+    /// release date, `None` if unknown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<Date>,
+    /// downloadable files, empty if unknown.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<GoFile>,
+}
+
+impl Release {
+    /// a release only known by its version, stable if it is not a prerelease.
+    pub fn new(version: impl Into<String>) -> Self {
+        let version = version.into();
+        let stable = toolchain::semantic(&version).is_ok_and(|v| v.pre.is_empty());
+        Self {
+            version,
+            stable,
+            date: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// find the file by file name.
+    pub fn file(&self, filename: &str) -> Option<&GoFile> {
+        self.files.iter().find(|v| v.filename == filename)
+    }
+
+    /// whether an archive file exists for the platform, `None` if the files are unknown.
+    pub fn has_archive(&self, os: &str, arch: &str) -> Option<bool> {
+        (!self.files.is_empty()).then(|| {
+            self.files
+                .iter()
+                .any(|v| v.kind == FileKind::Archive && v.os == os && v.arch == arch)
+        })
+    }
+
+    /// group the files by version into releases, sorted by version.
+    pub fn from_files<I>(files: I) -> Vec<Release>
+    where
+        I: IntoIterator<Item = (String, GoFile, Option<Date>)>,
+    {
+        let mut releases: Vec<Release> = Vec::new();
+        for (version, file, date) in files {
+            let idx = match releases.iter().position(|v| v.version == version) {
+                Some(idx) => idx,
+                None => {
+                    releases.push(Release::new(version.as_str()));
+                    releases.len() - 1
+                }
+            };
+            let release = &mut releases[idx];
+            // the earliest date of the files.
+            release.date = match (release.date, date) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            release.files.push(file);
+        }
+        Self::sort(&mut releases);
+        releases
+    }
+
+    /// sort releases by semantic version, releases without semantic version are dropped.
+    pub fn sort(releases: &mut Vec<Release>) {
+        releases.retain(|v| toolchain::semantic(&v.version).is_ok());
+        releases.sort_by(|a, b| {
+            match (
+                toolchain::semantic(&a.version),
+                toolchain::semantic(&b.version),
+            ) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => Ordering::Equal,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::civil::date;
+
+    use super::{FileKind, GoFile, Release};
+
+    #[test]
+    fn test_from_files() {
+        let file = |name: &str, size| {
+            let (version, mut file) = GoFile::parse(name).expect("go file");
+            file.size = size;
+            (version.to_owned(), file)
+        };
+        assert_eq!(
+            GoFile::parse("go1.21.5.src.tar.gz").unwrap().1.kind,
+            FileKind::Source
+        );
+        assert_eq!(
+            GoFile::parse("go1.21.5.darwin-arm64.pkg").unwrap().1.kind,
+            FileKind::Installer
+        );
+        assert!(GoFile::parse("go1.21.5.linux-amd64.tar.gz.sha256").is_none());
+
+        let releases = Release::from_files(
+            [
+                (file("go1.22rc1.linux-amd64.tar.gz", 3), None),
+                (
+                    file("go1.21.5.linux-amd64.tar.gz", 1),
+                    Some(date(2023, 12, 6)),
+                ),
+                (file("go1.21.5.src.tar.gz", 2), Some(date(2023, 12, 5))),
+                // not a semantic version
+                (("tip".to_owned(), GoFile::new("tip.tar.gz", "", "")), None),
+            ]
+            .map(|((version, file), date)| (version, file, date)),
+        );
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].version, "1.21.5");
+        assert!(releases[0].stable);
+        assert_eq!(releases[0].date, Some(date(2023, 12, 5)));
+        assert_eq!(releases[0].files.len(), 2);
+        assert_eq!(
+            releases[0].file("go1.21.5.linux-amd64.tar.gz").map(|v| (
+                v.os.as_str(),
+                v.arch.as_str(),
+                v.size
+            )),
+            Some(("linux", "amd64", 1))
+        );
+        assert_eq!(releases[1].version, "1.22rc1");
+        assert!(!releases[1].stable);
+
+        assert_eq!(releases[0].has_archive("linux", "amd64"), Some(true));
+        assert_eq!(releases[0].has_archive("darwin", "arm64"), Some(false));
+        assert_eq!(Release::new("1.21.5").has_archive("linux", "amd64"), None);
+    }
+
+    #[test]
+    fn test_file_kind() {
+        let kind = |json: &str| serde_json::from_str::<GoFile>(json).unwrap().kind;
+        assert_eq!(
+            kind(r#"{"filename":"go1.21.5.linux-amd64.tar.gz","kind":"archive"}"#),
+            FileKind::Archive
+        );
+        assert_eq!(
+            kind(r#"{"filename":"go1.21.5.darwin-arm64.pkg","kind":"installer"}"#),
+            FileKind::Installer
+        );
+        // a new upstream kind, or missing
+        assert_eq!(
+            kind(r#"{"filename":"go1.21.5.linux-amd64.sbom","kind":"sbom"}"#),
+            FileKind::Unknown
+        );
+        assert_eq!(
+            kind(r#"{"filename":"go1.21.5.linux-amd64.tar.gz"}"#),
+            FileKind::Unknown
+        );
+        let file = GoFile::new("go1.21.5.src.tar.gz", "", "");
+        assert_eq!(
+            serde_json::to_value(&file).unwrap()["kind"],
+            serde_json::json!("source")
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use std::fs;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use jiff::civil::Date;
+use regex::Regex;
+use reqwest::blocking::Client;
+use scraper::{ElementRef, Html, Selector};
+use semver::Version;
+use sha2::{Digest, Sha256};
+
+use crate::consts;
+use crate::dir::Dir;
+use crate::registries::registry_index::file_index::file_url_to_path;
+use crate::toolchain;
+
+/// path of the release history page to the base url.
+const RELEASE_HISTORY_PATH: &str = "doc/devel/release";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+static RELEASED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(released (\d{4}-\d{2}-\d{2})\)").unwrap());
+static SECURITY_FIXES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"security fix(?:es)? to (.+?)(?:, as well as |$)").unwrap());
+static FIXES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"fix(?:es)? to (.+)$").unwrap());
+static LIST_SEPARATOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r",\s*(?:and\s+)?|\s+and\s+").unwrap());
+
+/// the notes of a release on the release history page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseNote {
+    /// version, such as `1.22.1`.
+    pub version: String,
+    pub semver: Version,
+    pub date: Option<Date>,
+    /// the text of the notes.
+    pub summary: String,
+    /// packages of the security fixes.
+    pub security_fixes: Vec<String>,
+    /// packages and tools of the bug fixes.
+    pub bug_fixes: Vec<String>,
+    /// the milestone of the fixed issues on the issue tracker.
+    pub issues: Option<String>,
+}
+
+/// the release history page of `{base}/doc/devel/release`, cached in `cache/release-notes`.
+#[derive(Debug, Clone)]
+pub struct ReleaseHistory {
+    url: String,
+    refresh: bool,
+}
+
+impl ReleaseHistory {
+    /// new from the base url, such as `https://go.dev`, a mirror or `file:///srv/go.dev`.
+    pub fn new(base: &str, refresh: bool) -> Self {
+        Self {
+            url: format!("{}/{RELEASE_HISTORY_PATH}", base.trim_end_matches('/')),
+            refresh,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// the release notes, sorted by version.
+    /// the cached page is used if it has the notes of the version, otherwise fetch the page.
+    pub fn release_notes(&self, version: &Version) -> Result<Vec<ReleaseNote>, anyhow::Error> {
+        let cache_file = Dir::goup_home()?.cache_file(format!(
+            "release-notes/{}.html",
+            &hex::encode(Sha256::digest(&self.url))[..16]
+        ));
+        let cached = fs::read_to_string(&cache_file).ok().map(|v| parse(&v));
+        match cached {
+            Some(notes) if consts::offline() => return Ok(notes),
+            Some(notes) if !self.refresh && notes.iter().any(|v| &v.semver == version) => {
+                log::debug!("Use cached release notes of {}", self.url);
+                return Ok(notes);
+            }
+            _ => {}
+        }
+        let page = self.fetch()?;
+        if let Some(parent) = cache_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if let Err(e) = fs::write(&cache_file, &page) {
+            log::warn!("Write cached release notes failed: {e}");
+        }
+        Ok(parse(&page))
+    }
+
+    /// fetch the page, from the local copy or the url.
+    fn fetch(&self) -> Result<String, anyhow::Error> {
+        if self.url.starts_with("file://") {
+            let file = file_url_to_path(&self.url)?;
+            return fs::read_to_string(&file)
+                .map_err(|e| anyhow!("read {} failed: {e}", file.display()));
+        }
+        consts::ensure_online(format!("fetching {}", self.url))?;
+        let resp = Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()?
+            .get(&self.url)
+            .header("User-Agent", env!("CARGO_PKG_VERSION"))
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(anyhow!(
+                "Fetching {} failure, status {}",
+                self.url,
+                resp.status()
+            ));
+        }
+        Ok(resp.text()?)
+    }
+}
+
+/// parse the release history page, the major releases are `<h2 id="go1.22.0">` followed by
+/// the notes paragraph, the minor revisions are `<p id="go1.22.1">`.
+fn parse(html: &str) -> Vec<ReleaseNote> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("h2[id^=go], p[id^=go]").unwrap();
+    let milestone = Selector::parse("a[href*=milestone]").unwrap();
+    let mut notes: Vec<ReleaseNote> = document
+        .select(&selector)
+        .filter_map(|element| {
+            let version = element.value().id()?.trim_start_matches("go");
+            let semver = toolchain::semantic(version).ok()?;
+            let heading = text(element);
+            // 主版本的说明在标题后的段落中
+            let body = if element.value().name() == "h2" {
+                element
+                    .next_siblings()
+                    .filter_map(ElementRef::wrap)
+                    .next()
+                    .filter(|v| v.value().name() == "p")
+            } else {
+                Some(element)
+            };
+            let summary = body.map(text).unwrap_or_default();
+            // 次版本的段落以版本和发布日期开头
+            let summary = match RELEASED.find(&summary) {
+                Some(released) => summary[released.end()..].trim().to_owned(),
+                None => summary,
+            };
+            let date = RELEASED.captures(&heading).and_then(|v| v[1].parse().ok());
+            let (security_fixes, bug_fixes) = parse_fixes(&summary);
+            let issues = body
+                .and_then(|v| v.select(&milestone).next())
+                .and_then(|v| v.value().attr("href"))
+                .map(str::to_owned);
+            Some(ReleaseNote {
+                version: version.to_owned(),
+                semver,
+                date,
+                summary,
+                security_fixes,
+                bug_fixes,
+                issues,
+            })
+        })
+        .collect();
+    notes.sort_by(|a, b| a.semver.cmp(&b.semver));
+    notes.dedup_by(|a, b| a.semver == b.semver);
+    notes
+}
+
+/// the whitespace normalized text of the element.
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// the security and bug fixes of the notes, such as `includes security fixes to the net/http
+/// package, as well as bug fixes to the compiler and the runtime.`
+fn parse_fixes(summary: &str) -> (Vec<String>, Vec<String>) {
+    let sentence = summary.split(". See ").next().unwrap_or_default();
+    let sentence = sentence.trim_end_matches('.');
+    let security_fixes = SECURITY_FIXES
+        .captures(sentence)
+        .map(|v| parse_list(&v[1]))
+        .unwrap_or_default();
+    let bugs = match sentence.split_once(", as well as ") {
+        Some((_, bugs)) => Some(bugs),
+        None if security_fixes.is_empty() => Some(sentence),
+        None => None,
+    };
+    let bug_fixes = bugs
+        .and_then(|v| FIXES.captures(v))
+        .map(|v| parse_list(&v[1]))
+        .unwrap_or_default();
+    (security_fixes, bug_fixes)
+}
+
+/// `the compiler, the go command, and the net/http package` -> `compiler`, `go command`, `net/http`
+fn parse_list(list: &str) -> Vec<String> {
+    LIST_SEPARATOR
+        .split(list)
+        .map(|v| {
+            let v = v.trim();
+            let v = v.strip_prefix("the ").unwrap_or(v);
+            let v = v.strip_suffix(" packages").unwrap_or(v);
+            v.strip_suffix(" package").unwrap_or(v).to_owned()
+        })
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use jiff::civil::date;
+    use semver::Version;
+
+    use super::{ReleaseHistory, parse};
+    use crate::consts;
+
+    const PAGE: &str = r#"<html><body>
+<h2 id="go1.22.0">go1.22.0 (released 2024-02-06)</h2>
+<p>
+Go 1.22.0 is a major release of Go.
+Read the <a href="/doc/go1.22">Go 1.22 Release Notes</a> for more information.
+</p>
+<h3 id="go1.22.minor">Minor revisions</h3>
+<p id="go1.22.1">
+go1.22.1 (released 2024-03-05) includes security fixes to the <code>crypto/x509</code>,
+<code>html/template</code>, and <code>net/mail</code> packages, as well as bug fixes to the compiler,
+the <code>go</code> command, the runtime, and the <code>net/http</code> package.
+See the <a href="https://github.com/golang/go/issues?q=milestone%3AGo1.22.1+label%3ACherryPickApproved">Go
+1.22.1 milestone</a> on our issue tracker for details.
+</p>
+<p id="go1.22.2">
+go1.22.2 (released 2024-04-03) includes a security fix to the <code>net/http</code> package.
+</p>
+<p id="go1.22.3">
+go1.22.3 (released 2024-05-07) includes fixes to the compiler and the runtime.
+</p>
+</body></html>"#;
+
+    #[test]
+    fn test_parse() {
+        let notes = parse(PAGE);
+        assert_eq!(
+            notes.iter().map(|v| v.version.as_str()).collect::<Vec<_>>(),
+            vec!["1.22.0", "1.22.1", "1.22.2", "1.22.3"]
+        );
+        assert!(notes[0].summary.starts_with("Go 1.22.0 is a major release"));
+        assert_eq!(notes[0].date, Some(date(2024, 2, 6)));
+
+        let note = &notes[1];
+        assert_eq!(note.date, Some(date(2024, 3, 5)));
+        assert_eq!(
+            note.security_fixes,
+            vec!["crypto/x509", "html/template", "net/mail"]
+        );
+        assert_eq!(
+            note.bug_fixes,
+            vec!["compiler", "go command", "runtime", "net/http"]
+        );
+        assert!(
+            note.issues
+                .as_deref()
+                .unwrap()
+                .contains("milestone%3AGo1.22.1")
+        );
+
+        assert_eq!(notes[2].security_fixes, vec!["net/http"]);
+        assert!(notes[2].bug_fixes.is_empty());
+        assert!(notes[3].security_fixes.is_empty());
+        assert_eq!(notes[3].bug_fixes, vec!["compiler", "runtime"]);
+    }
+
+    #[test]
+    fn test_release_notes_cache() -> Result<(), anyhow::Error> {
+        let dir = tempfile::tempdir()?;
+        let site = dir.path().join("site");
+        std::fs::create_dir_all(site.join("doc/devel"))?;
+        std::fs::write(site.join("doc/devel/release"), PAGE)?;
+        let base = url::Url::from_directory_path(&site).unwrap();
+        temp_env::with_var(consts::GOUP_HOME, Some(dir.path()), || {
+            let history = ReleaseHistory::new(base.as_str(), false);
+            assert_eq!(history.release_notes(&Version::new(1, 22, 1))?.len(), 4);
+            // cached
+            std::fs::remove_dir_all(&site)?;
+            assert_eq!(history.release_notes(&Version::new(1, 22, 3))?.len(), 4);
+            // the cache has no notes of the version
+            assert!(history.release_notes(&Version::new(1, 22, 4)).is_err());
+            Ok(())
+        })
+    }
+}
@@ -7,6 +7,7 @@ use semver::{Op, Version, VersionReq};
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub enum Toolchain {
     Stable,
+    Oldstable,
     Unstable,
     Beta,
     Version(String),
@@ -18,7 +19,8 @@ impl FromStr for Toolchain {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
-            "stable" => Self::Stable,
+            "stable" | "latest" => Self::Stable,
+            "oldstable" => Self::Oldstable,
             "unstable" => Self::Unstable,
             "nightly" | "tip" | "gotip" => Self::Nightly,
             "beta" => Self::Beta,
@@ -93,119 +95,105 @@ pub fn semantic(ver: &str) -> Result<Version> {
     Ok(Version::parse(&name)?)
 }
 
-/// is_match_archived 是否版本请求匹配归档版本.
-pub fn is_match_archived(latest: &Version, secondary: &Version, ver_req: &VersionReq) -> bool {
-    if ver_req.comparators.len() != 1 {
-        return false;
+/// version request of the version, a bare Go version is resolved the way Go's toolchain selection does.
+/// 1.22        -> ~1.22 (latest 1.22.x)
+/// 1.22.5      -> =1.22.5
+/// go1.22rc1   -> =1.22.0-rc1
+/// ^1.22       -> ^1.22
+/// if caret, a bare version keeps the semver caret semantics, 1.22 -> ^1.22 (latest 1.x.x)
+pub fn version_req(ver: &str, caret: bool) -> String {
+    let bare = ver.trim().trim_start_matches("go");
+    let is_bare = bare.starts_with(|c: char| c.is_ascii_digit())
+        && bare.chars().all(|c| c.is_ascii_alphanumeric() || c == '.')
+        && semantic(bare).is_ok();
+    if !is_bare || caret {
+        return ver.to_owned();
     }
-    let comp = ver_req.comparators.first().unwrap();
-    if !comp.pre.is_empty() {
-        return false;
+    match semantic(bare) {
+        Ok(semver) if !semver.pre.is_empty() => format!("={semver}"),
+        _ if bare.chars().filter(|&v| v == '.').count() < 2 => format!("~{bare}"),
+        _ => format!("={bare}"),
     }
-    let major = comp.major;
-    match comp.op {
-        // = 精确匹配
-        //  =1.24.1 精确匹配
-        //  =1.24 匹配1.24.*
-        //  =1 匹配1.*.*
-        Op::Exact => match (comp.minor, comp.patch) {
-            // =1.24.1
-            (Some(minor), Some(patch)) => {
-                // 主版本号 < 次新版本号
-                // 主版本号 == 次新版本号 且 (次版号比次新版本的小 或则 次版本号一致, 修订版版本 < 次修订版本号)
-                // 主版本号 == 最新版本号 且 次版号 == 最新版本号 且 修订版版本 <= 最新版本号
-                major < secondary.major
-                    || (major == secondary.major
-                        && (minor < secondary.minor
-                            || (minor == secondary.minor && patch <= secondary.patch)))
-                    || (major == latest.major && minor == latest.minor && patch <= latest.patch)
-            }
-            // =1.24
-            (Some(minor), None) => {
-                major < secondary.major || (major == secondary.major && minor < secondary.minor)
-            }
-            (None, None) => major < secondary.major,
-            _ => false,
-        },
-        // <= 小于等于
-        //  <=1.24.1 匹配 <=1.24.1
-        //  <=1.24   匹配 1.24.*
-        //  <=1      匹配 1.*.*
-        Op::LessEq => match (comp.minor, comp.patch) {
-            // <=1.24.1
-            (Some(minor), Some(patch)) => {
-                // 主版本号 < 次新版本号
-                // 主版本号 == 次新版本号 且 (次版号比次新版本的小 或则 次版本号一致, 修订版版本 < 次修订版本号)
-                // 主版本号 == 最新版本号 且 次版号 == 最新版本号 且 修订版版本 <= 最新版本号
-                major < secondary.major
-                    || (major == secondary.major
-                        && (minor < secondary.minor
-                            || (minor == secondary.minor && patch <= secondary.patch)))
-                    || (major == latest.major && minor == latest.minor && patch <= latest.patch)
-            }
-            // <=1.24
-            (Some(minor), None) => {
-                major < secondary.major || (major == secondary.major && minor < secondary.minor)
-            }
-            (None, None) => major < secondary.major,
-            _ => false,
-        },
-        // < 小于
-        //  <1.24.1 匹配 <1.24.1
-        //  <1.24   匹配 <1.24.0即(1.23.*)
-        //  <1      匹配 <1.0.0即(0.*.*)
-        Op::Less => match (comp.minor, comp.patch) {
-            (Some(minor), Some(patch)) => {
-                major < secondary.major
-                    || (major == secondary.major
-                        && (minor < secondary.minor
-                            || (minor == secondary.minor && (patch - 1) <= secondary.patch)))
-                    || (major == latest.major
-                        && minor == latest.minor
-                        && (patch - 1) <= latest.patch)
-            }
-            (Some(minor), None) => {
-                major < secondary.major || (major == secondary.major && minor - 1 < secondary.minor)
-            }
-            (None, None) => major - 1 < secondary.major,
-            _ => false,
-        },
-        // * 通配符
-        //  1.24.* 匹配 1.24.*
-        //  1.*    匹配 1.*.*
-        //  *      匹配 最新版本
-        Op::Wildcard => {
-            if let Some(minor) = comp.minor {
-                major < secondary.major || (major == secondary.major && minor < secondary.minor)
-            } else {
-                major < secondary.major
-            }
-        }
-        // ~ 不改变主次版本的最新版本
-        //  ~1.24.1 匹配1.24.*
-        //  ~1.24   匹配1.24.*
-        //  ~1      匹配1.*.*
-        Op::Tilde => {
-            if let Some(minor) = comp.minor {
-                major < secondary.major || (major == secondary.major && minor < secondary.minor)
-            } else {
-                major < secondary.major
-            }
-        }
-        // ^ 不改变主版本的最新版本
-        //  ^1.24.1, ^1.24, ^1 均匹配 1.*.*
-        //  1.24.1, 1.24, 1 均匹配 1.*.*
-        Op::Caret => comp.major < secondary.major,
-        _ => false, // Op::Greater | Op::GreaterEq
+}
+
+/// is_match_archived 缓存中的最佳匹配版本是否为最终版本, 即将来发布的版本不会既匹配版本请求又比它更新.
+/// 将来可能发布的版本: 最新和次新稳定版本所在次版本的后续补丁版本, 以及更新的次版本.
+pub fn is_match_archived(
+    latest: &Version,
+    secondary: &Version,
+    ver_req: &VersionReq,
+    best: &Version,
+) -> bool {
+    // 预发布版本无法枚举, 只有精确的预发布版本是最终版本
+    if ver_req.comparators.iter().any(|v| !v.pre.is_empty()) {
+        return matches!(
+            ver_req.comparators.as_slice(),
+            [comp] if comp.op == Op::Exact && comp.minor.is_some() && comp.patch.is_some()
+        );
     }
+    // 匹配版本的集合是若干区间的交集, 若交集非空, 其最小值必为某个区间的下界,
+    // 所以只需检查版本请求, 最佳匹配版本和将来版本区间的下界附近的版本.
+    let mut points = vec![latest.clone(), secondary.clone(), best.clone()];
+    points.extend(ver_req.comparators.iter().map(|v| {
+        Version::new(
+            v.major,
+            v.minor.unwrap_or_default(),
+            v.patch.unwrap_or_default(),
+        )
+    }));
+    !points
+        .iter()
+        .flat_map(|v| {
+            [
+                Version::new(0, 0, 0),
+                Version::new(v.major, 0, 0),
+                Version::new(v.major, v.minor, 0),
+                Version::new(v.major, v.minor, v.patch),
+                Version::new(v.major, v.minor, v.patch + 1),
+                Version::new(v.major, v.minor + 1, 0),
+                Version::new(v.major + 1, 0, 0),
+            ]
+        })
+        .any(|v| is_future(latest, secondary, &v) && &v > best && ver_req.matches(&v))
+}
+
+/// is_future 是否为将来可能发布的版本.
+fn is_future(latest: &Version, secondary: &Version, ver: &Version) -> bool {
+    let line = (ver.major, ver.minor);
+    (line == (latest.major, latest.minor) && ver.patch > latest.patch)
+        || (line == (secondary.major, secondary.minor) && ver.patch > secondary.patch)
+        || line > (latest.major, latest.minor)
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Ok;
+    use proptest::prelude::*;
 
     use super::*;
 
+    /// released versions up to latest 1.25.2 and secondary 1.24.2.
+    fn released() -> impl Iterator<Item = Version> {
+        let old = (8..=9).flat_map(|minor| (0..=9).map(move |patch| Version::new(0, minor, patch)));
+        let lines = (0..=25u64).flat_map(|minor| {
+            let patches = match minor {
+                24 | 25 => 2,
+                _ => 9,
+            };
+            (0..=patches).map(move |patch| Version::new(1, minor, patch))
+        });
+        old.chain(lines)
+            .chain([Version::parse("1.25.0-rc1").unwrap()])
+    }
+
+    /// whether the request resolves from the released versions.
+    fn archived(latest: &Version, secondary: &Version, ver_req: &VersionReq) -> bool {
+        released()
+            .filter(|v| ver_req.matches(v))
+            .max()
+            .is_some_and(|best| is_match_archived(latest, secondary, ver_req, &best))
+    }
+
     #[test]
     fn test_normalize() {
         assert_eq!(normalize("1.21.1"), "go1.21.1",);
@@ -243,6 +231,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_toolchain() {
+        assert_eq!("latest".parse::<Toolchain>().unwrap(), Toolchain::Stable);
+        assert_eq!(
+            "oldstable".parse::<Toolchain>().unwrap(),
+            Toolchain::Oldstable
+        );
+        assert_eq!(
+            "1.22".parse::<Toolchain>().unwrap(),
+            Toolchain::Version("1.22".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_version_req() {
+        assert_eq!(version_req("1", false), "~1");
+        assert_eq!(version_req("1.22", false), "~1.22");
+        assert_eq!(version_req("go1.22", false), "~1.22");
+        assert_eq!(version_req("1.22.5", false), "=1.22.5");
+        assert_eq!(version_req("1.22rc1", false), "=1.22.0-rc1");
+        assert_eq!(version_req("=1.22.5", false), "=1.22.5");
+        assert_eq!(version_req("^1.22", false), "^1.22");
+        assert_eq!(version_req("1.22.*", false), "1.22.*");
+        assert_eq!(version_req(">=1.21, <1.23", false), ">=1.21, <1.23");
+        assert_eq!(version_req("1.22", true), "1.22");
+        assert_eq!(version_req("1.22.5", true), "1.22.5");
+
+        let matches = |req: &str, ver: &str| {
+            VersionReq::parse(&version_req(req, false))
+                .unwrap()
+                .matches(&semantic(ver).unwrap())
+        };
+        assert!(matches("1.22", "1.22.9"));
+        assert!(!matches("1.22", "1.23.0"));
+        assert!(!matches("1.22.5", "1.22.6"));
+        assert!(matches("1.22rc1", "1.22rc1"));
+    }
+
     #[test]
     fn test_all_go_version_semantic() {
         let go_versions = [
@@ -585,78 +611,50 @@ mod tests {
         let secondary = semantic("1.24.2")?;
 
         // = 精确匹配
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("=0.9.9")?
-        ));
-        assert!(is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("=0.9.9")?));
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("=1.23.9")?
         ));
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("=1.24.2")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("=1.24.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("=1.24.9")?
         ));
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("=1.25.2")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("=1.25.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("=1.25.9")?
         ));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("=0.9")?
-        ));
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("=1.23")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("=1.24")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("=1.25")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("=0.9")?));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("=1.23")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("=1.24")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("=1.25")?));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("=0")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("=1")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("=0")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("=1")?));
         Ok(())
     }
     #[test]
@@ -665,78 +663,50 @@ mod tests {
         let secondary = semantic("1.24.2")?;
 
         // ~ 波浪匹配
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("~0.9.9")?
-        ));
-        assert!(is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("~0.9.9")?));
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("~1.23.9")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("~1.24.2")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("~1.24.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("~1.24.9")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("~1.25.2")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("~1.25.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("~1.25.9")?
         ));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("~0.9")?
-        ));
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("~1.23")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("~1.24")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("~1.25")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("~0.9")?));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("~1.23")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("~1.24")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("~1.25")?));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("~0")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("~1")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("~0")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("~1")?));
 
         Ok(())
     }
@@ -746,79 +716,63 @@ mod tests {
         let secondary = semantic("1.24.2")?;
 
         // <= 小于等于匹配
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=0.9.9")?
         ));
 
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.23.9")?
         ));
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.25.2")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.25.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.25.9")?
         ));
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.24.2")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.24.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.24.9")?
         ));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<=0.9")?
-        ));
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<=1.23")?
-        ));
-        assert!(!is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<=0.9")?));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<=1.23")?));
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.24")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<=1.25")?
         ));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<=0")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<=1")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<=0")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("<=1")?));
         Ok(())
     }
 
@@ -828,83 +782,55 @@ mod tests {
         let secondary = semantic("1.24.2")?;
 
         // < 小于匹配
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<0.9.9")?
-        ));
-        assert!(is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<0.9.9")?));
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.23.2")?
         ));
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.24.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.24.4")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.24.9")?
         ));
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.25.2")?
         ));
-        assert!(is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.25.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.25.4")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("<1.25.9")?
         ));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<0.9")?
-        ));
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<1.23")?
-        ));
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<1.24")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<1.25")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<0.9")?));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<1.23")?));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<1.24")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("<1.25")?));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<1")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("<2")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("<1")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("<2")?));
         Ok(())
     }
 
@@ -914,37 +840,21 @@ mod tests {
         let secondary = semantic("1.24.2")?;
 
         // * 通配符匹配
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("0.9.*")?
-        ));
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("1.23.*")?
-        ));
-        assert!(!is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("0.9.*")?));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("1.23.*")?));
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("1.24.*")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("1.25.*")?
         ));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("0.*")?
-        ));
-        assert!(!is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("1.*")?
-        ));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("0.*")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("1.*")?));
         Ok(())
     }
 
@@ -954,88 +864,140 @@ mod tests {
         let secondary = semantic("1.24.2")?;
 
         // ^ 匹配
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("^0.9.9")?
-        ));
-        assert!(!is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("^0.9.9")?));
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("^1.23.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("^1.24.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("^1.25.3")?
         ));
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("0.9.9")?
-        ));
-        assert!(!is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("0.9.9")?));
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("1.23.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("1.24.3")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
             &VersionReq::parse("1.25.3")?
         ));
 
-        assert!(is_match_archived(
-            &latest,
-            &secondary,
-            &VersionReq::parse("^0.9")?
-        ));
-        assert!(!is_match_archived(
+        assert!(archived(&latest, &secondary, &VersionReq::parse("^0.9")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("^1.23")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("^1.24")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("^1.25")?));
+        assert!(archived(&latest, &secondary, &VersionReq::parse("0.9")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("1.23")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("1.24")?));
+        assert!(!archived(&latest, &secondary, &VersionReq::parse("1.25")?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_match_archived_multiple() -> Result<(), anyhow::Error> {
+        let latest = semantic("1.25.2")?;
+        let secondary = semantic("1.24.2")?;
+
+        assert!(archived(
             &latest,
             &secondary,
-            &VersionReq::parse("^1.23")?
+            &VersionReq::parse(">=1.21, <1.23")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
-            &VersionReq::parse("^1.24")?
+            &VersionReq::parse(">=1.21, <1.25")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
-            &VersionReq::parse("^1.25")?
+            &VersionReq::parse(">=1.21.3")?
         ));
-        assert!(is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
-            &VersionReq::parse("0.9")?
+            &VersionReq::parse(">1.25.2")?
         ));
-        assert!(!is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
-            &VersionReq::parse("1.23")?
+            &VersionReq::parse(">1.22.1, <=1.22.5")?
         ));
-        assert!(!is_match_archived(
+        assert!(archived(
             &latest,
             &secondary,
-            &VersionReq::parse("1.24")?
+            &VersionReq::parse("=1.25.0-rc1")?
         ));
-        assert!(!is_match_archived(
+        assert!(!archived(
             &latest,
             &secondary,
-            &VersionReq::parse("1.25")?
+            &VersionReq::parse(">=1.25.0-rc1")?
         ));
-
         Ok(())
     }
+
+    /// a version request of up to 3 comparators.
+    fn version_req_strategy() -> impl Strategy<Value = VersionReq> {
+        let op = prop::sample::select(vec!["=", ">", ">=", "<", "<=", "~", "^", ""]);
+        let comparator = (op, 0..=2u64, prop::option::of(0..=11u64), 0..=12u64).prop_map(
+            |(op, major, minor, patch)| match (minor, patch) {
+                (None, _) => format!("{op}{major}"),
+                (Some(minor), 12) if op.is_empty() => format!("{major}.{minor}.*"),
+                (Some(minor), 11) => format!("{op}{major}.{minor}"),
+                (Some(minor), patch) => format!("{op}{major}.{minor}.{patch}"),
+            },
+        );
+        prop::collection::vec(comparator, 1..=3)
+            .prop_filter_map("invalid request", |v| VersionReq::parse(&v.join(", ")).ok())
+    }
+
+    proptest! {
+        /// the request resolves from the cache iff no future version could match the request
+        /// and be greater than the best cached match, checked against all versions.
+        #[test]
+        fn prop_is_match_archived(
+            minor in 1..=10u64,
+            latest_patch in 0..=11u64,
+            secondary_patch in 0..=11u64,
+            ver_req in version_req_strategy(),
+        ) {
+            let latest = Version::new(1, minor, latest_patch);
+            let secondary = Version::new(1, minor - 1, secondary_patch);
+            let all = (0..=3u64).flat_map(|major| {
+                (0..=13u64).flat_map(move |minor| {
+                    (0..=13u64).map(move |patch| Version::new(major, minor, patch))
+                })
+            });
+            let (future, cached): (Vec<_>, Vec<_>) =
+                all.partition(|v| is_future(&latest, &secondary, v));
+            if let Some(best) = cached.iter().filter(|v| ver_req.matches(v)).max() {
+                let expected = !future.iter().any(|v| v > best && ver_req.matches(v));
+                prop_assert_eq!(
+                    is_match_archived(&latest, &secondary, &ver_req, best),
+                    expected,
+                    "{} latest {} secondary {} best {}",
+                    ver_req,
+                    latest,
+                    secondary,
+                    best
+                );
+            }
+        }
+    }
 }
@@ -3,6 +3,7 @@ use std::fs::DirEntry;
 
 use anyhow::Result;
 use anyhow::anyhow;
+use semver::VersionReq;
 
 use crate::consts;
 use crate::dir::Dir;
@@ -73,6 +74,19 @@ impl Version {
         Ok(version_dirs)
     }
 
+    /// the highest installed version satisfying the version request.
+    pub fn match_installed<'a>(
+        versions: &'a [Version],
+        ver_req: &VersionReq,
+    ) -> Option<&'a Version> {
+        versions
+            .iter()
+            .filter_map(|v| toolchain::semantic(&v.version).ok().map(|s| (s, v)))
+            .filter(|(s, _)| ver_req.matches(s))
+            .max_by(|a, b| a.0.cmp(&b.0))
+            .map(|v| v.1)
+    }
+
     /// set active go version
     pub fn set_go_version(version: &str) -> Result<(), anyhow::Error> {
         let version = toolchain::normalize(version);
@@ -144,30 +158,6 @@ impl Version {
         Ok(current)
     }
 
-    /// list `${HOME}/.goup/cache` directory items(only file, ignore directory).
-    pub fn list_cache(contain_sha256: Option<bool>) -> Result<Vec<String>, anyhow::Error> {
-        let goup_home = Dir::goup_home()?;
-        // may be .goup or .goup/cache not exist
-        if !goup_home.exists() || !goup_home.cache().exists() {
-            return Ok(Vec::new());
-        }
-        let contain_sha256 = contain_sha256.unwrap_or_default();
-        let dir: Result<Vec<DirEntry>, _> = goup_home.cache().read_dir()?.collect();
-        let mut archive_files: Vec<_> = dir?
-            .iter()
-            .filter_map(|v| {
-                if v.path().is_dir() {
-                    return None;
-                }
-                let filename = v.file_name();
-                let filename = filename.to_string_lossy();
-                (contain_sha256 || !filename.ends_with(".sha256")).then(|| filename.to_string())
-            })
-            .collect();
-        archive_files.sort();
-        Ok(archive_files)
-    }
-
     /// remove `${HOME}/.goup/cache` directory.
     pub fn remove_cache() -> Result<(), anyhow::Error> {
         let dl_dir = Dir::goup_home()?.cache();
@@ -186,3 +176,29 @@ impl Version {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use semver::VersionReq;
+
+    use super::Version;
+
+    #[test]
+    fn test_match_installed() {
+        let versions =
+            ["1.21.0", "1.22.1", "1.22.10", "1.22.2", "1.23rc1", "tip"].map(|v| Version {
+                version: v.to_owned(),
+                default: false,
+                session: false,
+            });
+        let matched = |req: &str| {
+            Version::match_installed(&versions, &VersionReq::parse(req).unwrap())
+                .map(|v| v.version.as_str())
+        };
+        assert_eq!(matched("~1.22"), Some("1.22.10"));
+        assert_eq!(matched(">=1.21, <1.22"), Some("1.21.0"));
+        assert_eq!(matched("^1"), Some("1.22.10"));
+        assert_eq!(matched("=1.23.0-rc1"), Some("1.23rc1"));
+        assert_eq!(matched("~1.24"), None);
+    }
+}
@@ -0,0 +1,355 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use reqwest::blocking::Client;
+use semver::Version;
+use serde::Deserialize;
+
+use crate::consts;
+use crate::registries::registry_index::file_index::file_url_to_path;
+
+/// modules of the Go standard library and toolchain in the Go vulnerability database.
+const GO_MODULES: [&str; 2] = ["stdlib", "toolchain"];
+/// concurrent requests of fetching the entries.
+const CONCURRENCY: usize = 8;
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// a module of `index/modules.json`.
+#[derive(Debug, Deserialize)]
+struct ModuleVulns {
+    path: String,
+    #[serde(default)]
+    vulns: Vec<VulnRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VulnRef {
+    id: String,
+    /// the latest fixed version, if any.
+    #[serde(default)]
+    fixed: Option<String>,
+}
+
+/// a vulnerability entry of `ID/{id}.json` in OSV format.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Osv {
+    pub id: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    affected: Vec<Affected>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Affected {
+    package: Package,
+    #[serde(default)]
+    ranges: Vec<Range>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Package {
+    name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Range {
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Event {
+    #[serde(default)]
+    introduced: Option<String>,
+    #[serde(default)]
+    fixed: Option<String>,
+    #[serde(default)]
+    last_affected: Option<String>,
+}
+
+impl Osv {
+    /// if the Go version is affected, returns the minimum fixed version, `None` if not fixed yet.
+    pub fn affects(&self, version: &Version) -> Option<Option<Version>> {
+        let mut affected = None;
+        for range in self
+            .affected
+            .iter()
+            .filter(|v| GO_MODULES.contains(&v.package.name.as_str()))
+            .flat_map(|v| &v.ranges)
+        {
+            let Some(fixed) = range.affects(version) else {
+                continue;
+            };
+            affected = match (affected, fixed) {
+                (Some(Some(a)), Some(b)) => Some(Some(std::cmp::max(a, b))),
+                (Some(None), _) | (_, None) => Some(None),
+                (None, fixed) => Some(fixed),
+            };
+        }
+        affected
+    }
+
+    /// the CVE aliases.
+    pub fn cves(&self) -> impl Iterator<Item = &str> {
+        self.aliases
+            .iter()
+            .filter(|v| v.starts_with("CVE-"))
+            .map(String::as_str)
+    }
+}
+
+impl Range {
+    /// if the Go version is in the range, returns the next fixed version.
+    fn affects(&self, version: &Version) -> Option<Option<Version>> {
+        // (version, order at the same version, event)
+        let mut events: Vec<(Version, u8, &Event)> = self
+            .events
+            .iter()
+            .filter_map(|v| {
+                let (ver, order) = match (&v.introduced, &v.fixed, &v.last_affected) {
+                    (Some(ver), _, _) => (ver, 0),
+                    (_, Some(ver), _) => (ver, 1),
+                    (_, _, Some(ver)) => (ver, 2),
+                    _ => return None,
+                };
+                osv_version(ver).map(|ver| (ver, order, v))
+            })
+            .collect();
+        events.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+        let mut affected = false;
+        for (ver, _, event) in &events {
+            match ver.cmp(version) {
+                Ordering::Greater => break,
+                Ordering::Equal if event.last_affected.is_some() => break,
+                _ => affected = event.introduced.is_some(),
+            }
+        }
+        affected.then(|| {
+            events
+                .iter()
+                .find(|(ver, _, event)| event.fixed.is_some() && ver > version)
+                .map(|v| v.0.clone())
+        })
+    }
+}
+
+/// parse the semver of the Go vulnerability database, `0`, `1.21.0-rc.2` or `1.21.0-0`,
+/// comparable with [`crate::toolchain::semantic`] such as `1.21.0-rc2`.
+fn osv_version(ver: &str) -> Option<Version> {
+    if ver == "0" {
+        return Some(Version::new(0, 0, 0));
+    }
+    let ver = ver
+        .trim_start_matches('v')
+        .replace("-rc.", "-rc")
+        .replace("-beta.", "-beta");
+    Version::parse(&ver).ok()
+}
+
+/// the Go vulnerability database, a url such as `https://vuln.go.dev`, or a local copy.
+#[derive(Debug, Clone)]
+pub struct VulnDb {
+    url: String,
+}
+
+impl VulnDb {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// the vulnerabilities of the Go standard library and toolchain,
+    /// skip the ones fixed before the minimum version.
+    pub fn go_vulns(&self, min_version: &Version) -> Result<Vec<Osv>, anyhow::Error> {
+        let modules: Vec<ModuleVulns> = serde_json::from_slice(&self.fetch("index/modules.json")?)?;
+        let mut ids: Vec<&str> = modules
+            .iter()
+            .filter(|v| GO_MODULES.contains(&v.path.as_str()))
+            .flat_map(|v| &v.vulns)
+            .filter(|v| {
+                v.fixed
+                    .as_deref()
+                    .and_then(osv_version)
+                    .is_none_or(|fixed| &fixed > min_version)
+            })
+            .map(|v| v.id.as_str())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        log::debug!(
+            "Fetching {} vulnerability entries from {}",
+            ids.len(),
+            self.url
+        );
+
+        let chunk_size = ids.len().div_ceil(CONCURRENCY).max(1);
+        let entries = thread::scope(|s| {
+            let handles: Vec<_> = ids
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|id| {
+                                let entry = self.fetch(&format!("ID/{id}.json"))?;
+                                serde_json::from_slice::<Osv>(&entry)
+                                    .map_err(|e| anyhow!("invalid vulnerability entry {id}: {e}"))
+                            })
+                            .collect::<Result<Vec<_>, anyhow::Error>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|v| v.join().map_err(|_| anyhow!("fetching entries panicked"))?)
+                .collect::<Result<Vec<_>, anyhow::Error>>()
+        })?;
+        Ok(entries.into_iter().flatten().collect())
+    }
+
+    /// fetch the file of the database, from the local copy or the url.
+    fn fetch(&self, path: &str) -> Result<Vec<u8>, anyhow::Error> {
+        if let Some(dir) = self.local_dir()? {
+            let file = dir.join(path);
+            return fs::read(&file).map_err(|e| anyhow!("read {} failed: {e}", file.display()));
+        }
+        let url = format!("{}/{path}", self.url);
+        consts::ensure_online(format!("fetching {url}"))?;
+        let resp = Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()?
+            .get(&url)
+            .header("User-Agent", env!("CARGO_PKG_VERSION"))
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("Fetching {url} failure, status {}", resp.status()));
+        }
+        let mut body = Vec::new();
+        resp.take(64 << 20).read_to_end(&mut body)?;
+        Ok(body)
+    }
+
+    /// the directory of the local copy, `file:///srv/vulndb` or `/srv/vulndb`.
+    fn local_dir(&self) -> Result<Option<PathBuf>, anyhow::Error> {
+        if self.url.starts_with("file://") {
+            return file_url_to_path(&self.url).map(Some);
+        }
+        if self.url.starts_with("http://") || self.url.starts_with("https://") {
+            return Ok(None);
+        }
+        Ok(Some(PathBuf::from(&self.url)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use semver::Version;
+    use serde_json::json;
+
+    use super::{Osv, VulnDb};
+    use crate::toolchain;
+
+    fn osv(id: &str, package: &str, events: serde_json::Value) -> serde_json::Value {
+        json!({
+            "id": id,
+            "summary": format!("summary of {id}"),
+            "aliases": ["CVE-2023-1", "GHSA-xxxx"],
+            "affected": [{
+                "package": {"name": package, "ecosystem": "Go"},
+                "ranges": [{"type": "SEMVER", "events": events}],
+            }],
+        })
+    }
+
+    #[test]
+    fn test_affects() {
+        let entry: Osv = serde_json::from_value(osv(
+            "GO-2023-0001",
+            "stdlib",
+            json!([
+                {"introduced": "0"},
+                {"fixed": "1.20.10"},
+                {"introduced": "1.21.0-0"},
+                {"fixed": "1.21.3"},
+            ]),
+        ))
+        .unwrap();
+        let affects = |v: &str| entry.affects(&toolchain::semantic(v).unwrap());
+        assert_eq!(affects("1.20.9"), Some(Some(Version::new(1, 20, 10))));
+        assert_eq!(affects("1.20.10"), None);
+        assert_eq!(affects("1.21rc2"), Some(Some(Version::new(1, 21, 3))));
+        assert_eq!(affects("1.21.2"), Some(Some(Version::new(1, 21, 3))));
+        assert_eq!(affects("1.21.3"), None);
+        assert_eq!(entry.cves().collect::<Vec<_>>(), vec!["CVE-2023-1"]);
+
+        let unfixed: Osv = serde_json::from_value(osv(
+            "GO-2023-0002",
+            "toolchain",
+            json!([{"introduced": "1.21.0"}]),
+        ))
+        .unwrap();
+        assert_eq!(
+            unfixed.affects(&Version::new(1, 22, 0)),
+            Some(None::<Version>)
+        );
+        assert_eq!(unfixed.affects(&Version::new(1, 20, 0)), None);
+        let module: Osv = serde_json::from_value(osv(
+            "GO-2023-0003",
+            "golang.org/x/net",
+            json!([{"introduced": "0"}]),
+        ))
+        .unwrap();
+        assert_eq!(module.affects(&Version::new(1, 20, 0)), None);
+    }
+
+    #[test]
+    fn test_go_vulns() -> Result<(), anyhow::Error> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir_all(dir.path().join("index"))?;
+        fs::create_dir_all(dir.path().join("ID"))?;
+        fs::write(
+            dir.path().join("index/modules.json"),
+            json!([
+                {"path": "stdlib", "vulns": [
+                    {"id": "GO-2022-0001", "fixed": "1.18.4"},
+                    {"id": "GO-2023-0001", "fixed": "1.21.3"},
+                ]},
+                {"path": "toolchain", "vulns": [{"id": "GO-2023-0002"}]},
+                {"path": "golang.org/x/net", "vulns": [{"id": "GO-2023-0003"}]},
+            ])
+            .to_string(),
+        )?;
+        for (id, package) in [("GO-2023-0001", "stdlib"), ("GO-2023-0002", "toolchain")] {
+            fs::write(
+                dir.path().join(format!("ID/{id}.json")),
+                osv(id, package, json!([{"introduced": "0"}])).to_string(),
+            )?;
+        }
+        let url = url::Url::from_directory_path(dir.path()).unwrap();
+        let mut ids = VulnDb::new(url.as_str())
+            .go_vulns(&Version::new(1, 20, 0))?
+            .into_iter()
+            .map(|v| v.id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec!["GO-2023-0001", "GO-2023-0002"]);
+        assert!(
+            VulnDb::new(&dir.path().to_string_lossy())
+                .go_vulns(&Version::new(1, 22, 0))
+                .is_ok()
+        );
+        Ok(())
+    }
+}